@@ -0,0 +1,381 @@
+const NONE: usize = usize::MAX;
+
+#[derive(Clone, Debug)]
+struct Node {
+    children: [usize; 2],
+    count: usize,
+}
+
+/// Binary trie storing a multiset of `BITS`-bit unsigned integers
+///
+/// Answers queries about the elements XOR-ed with a given value.
+/// Nodes are stored in a single arena and are never deallocated.
+///
+/// # Example
+///
+/// Maximum XOR of two elements of an array:
+///
+/// ```
+/// use cp_library::ds::binary_trie::BinaryTrie;
+///
+/// let arr = [3, 10, 5, 25, 2, 8];
+/// let mut trie = BinaryTrie::<5>::new();
+/// let mut best = 0;
+///
+/// for &x in &arr {
+///     if let Some(val) = trie.max_xor(x) {
+///         best = best.max(val);
+///     }
+///     trie.insert(x);
+/// }
+///
+/// assert_eq!(best, 28);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BinaryTrie<const BITS: usize> {
+    nodes: Vec<Node>,
+}
+
+impl<const BITS: usize> BinaryTrie<BITS> {
+    /// Creates an empty trie
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let trie = BinaryTrie::<30>::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_capacity(1)
+    }
+
+    /// Creates an empty trie, with space reserved for `nodes` nodes.
+    ///
+    /// Each insertion creates at most `BITS` nodes.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let trie = BinaryTrie::<30>::with_capacity(200_000 * 30);
+    /// ```
+    pub fn with_capacity(nodes: usize) -> Self {
+        debug_assert!(BITS <= 64);
+
+        let mut arena = Vec::with_capacity(nodes);
+        arena.push(Node {
+            children: [NONE; 2],
+            count: 0,
+        });
+        BinaryTrie { nodes: arena }
+    }
+
+    /// Returns the number of elements in the trie, counted with multiplicity
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.nodes[0].count
+    }
+
+    /// Returns [`true`] if the trie contains no elements
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn bit(x: u64, i: usize) -> usize {
+        ((x >> i) & 1) as usize
+    }
+
+    fn count_at(&self, node: usize) -> usize {
+        if node == NONE {
+            0
+        } else {
+            self.nodes[node].count
+        }
+    }
+
+    // Returns the leaf of `x`, if it was ever created
+    fn find(&self, x: u64) -> usize {
+        let mut node = 0;
+        for i in (0..BITS).rev() {
+            node = self.nodes[node].children[Self::bit(x, i)];
+            if node == NONE {
+                break;
+            }
+        }
+        node
+    }
+
+    /// Inserts `x` in the trie
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// trie.insert(5);
+    /// trie.insert(5);
+    /// assert_eq!(trie.count(5), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `x` does not fit in `BITS` bits.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// trie.insert(16);
+    /// ```
+    pub fn insert(&mut self, x: u64) {
+        debug_assert!(x.checked_shr(BITS as u32).unwrap_or(0) == 0);
+
+        let mut node = 0;
+        self.nodes[node].count += 1;
+        for i in (0..BITS).rev() {
+            let b = Self::bit(x, i);
+            if self.nodes[node].children[b] == NONE {
+                self.nodes.push(Node {
+                    children: [NONE; 2],
+                    count: 0,
+                });
+                self.nodes[node].children[b] = self.nodes.len() - 1;
+            }
+            node = self.nodes[node].children[b];
+            self.nodes[node].count += 1;
+        }
+    }
+
+    // Removes `cnt` copies of `x`, which must be present
+    fn remove(&mut self, x: u64, cnt: usize) {
+        let mut node = 0;
+        self.nodes[node].count -= cnt;
+        for i in (0..BITS).rev() {
+            node = self.nodes[node].children[Self::bit(x, i)];
+            self.nodes[node].count -= cnt;
+        }
+    }
+
+    /// Removes one copy of `x` from the trie.
+    /// Returns [`true`] if `x` was present.
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// trie.insert(5);
+    /// trie.insert(5);
+    /// assert!(trie.remove_one(5));
+    /// assert!(!trie.remove_one(6));
+    /// assert_eq!(trie.count(5), 1);
+    /// ```
+    pub fn remove_one(&mut self, x: u64) -> bool {
+        if self.count(x) == 0 {
+            return false;
+        }
+        self.remove(x, 1);
+        true
+    }
+
+    /// Removes all the copies of `x` from the trie.
+    /// Returns the number of removed elements.
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// trie.insert(5);
+    /// trie.insert(5);
+    /// assert_eq!(trie.remove_all(5), 2);
+    /// assert!(trie.is_empty());
+    /// ```
+    pub fn remove_all(&mut self, x: u64) -> usize {
+        let cnt = self.count(x);
+        if cnt > 0 {
+            self.remove(x, cnt);
+        }
+        cnt
+    }
+
+    /// Returns the number of copies of `x` in the trie
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    pub fn count(&self, x: u64) -> usize {
+        if x.checked_shr(BITS as u32).unwrap_or(0) != 0 {
+            return 0;
+        }
+        self.count_at(self.find(x))
+    }
+
+    // Returns the element y minimizing `x ^ y`
+    fn closest(&self, x: u64) -> Option<u64> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let (mut node, mut y) = (0, 0);
+        for i in (0..BITS).rev() {
+            let mut b = Self::bit(x, i);
+            if self.count_at(self.nodes[node].children[b]) == 0 {
+                b ^= 1;
+            }
+            node = self.nodes[node].children[b];
+            y |= (b as u64) << i;
+        }
+        Some(y)
+    }
+
+    /// Returns the minimum of `x ^ y` over the elements `y` of the trie,
+    /// or [`None`] if the trie is empty
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// assert_eq!(trie.min_xor(3), None);
+    /// trie.insert(5);
+    /// trie.insert(9);
+    /// assert_eq!(trie.min_xor(3), Some(6));
+    /// ```
+    pub fn min_xor(&self, x: u64) -> Option<u64> {
+        self.closest(x).map(|y| x ^ y)
+    }
+
+    /// Returns the maximum of `x ^ y` over the elements `y` of the trie,
+    /// or [`None`] if the trie is empty
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// assert_eq!(trie.max_xor(3), None);
+    /// trie.insert(5);
+    /// trie.insert(9);
+    /// assert_eq!(trie.max_xor(3), Some(10));
+    /// ```
+    pub fn max_xor(&self, x: u64) -> Option<u64> {
+        let mask = u64::MAX.checked_shr(64 - BITS as u32).unwrap_or(0);
+        self.closest(x ^ mask).map(|y| x ^ y)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest value of `y ^ mask` over the elements `y` of the trie,
+    /// counted with multiplicity, or [`None`] if the trie has at most `k` elements
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// trie.insert(1);
+    /// trie.insert(2);
+    /// trie.insert(2);
+    /// trie.insert(7);
+    /// assert_eq!(trie.kth_xor(0, 1), Some(2));
+    /// assert_eq!(trie.kth_xor(3, 0), Some(1));
+    /// assert_eq!(trie.kth_xor(3, 3), Some(4));
+    /// assert_eq!(trie.kth_xor(3, 4), None);
+    /// ```
+    pub fn kth_xor(&self, mask: u64, k: usize) -> Option<u64> {
+        if k >= self.len() {
+            return None;
+        }
+
+        let (mut node, mut k, mut val) = (0, k, 0);
+        for i in (0..BITS).rev() {
+            let mut b = Self::bit(mask, i);
+            let low = self.count_at(self.nodes[node].children[b]);
+            if k >= low {
+                k -= low;
+                b ^= 1;
+                val |= 1 << i;
+            }
+            node = self.nodes[node].children[b];
+        }
+        Some(val)
+    }
+
+    /// Returns the number of elements `y` of the trie, counted with multiplicity,
+    /// such that `x ^ y` is smaller than `k`
+    ///
+    /// Complexity: $\mathcal{O}(B)$ where:
+    /// - $B$ is the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::binary_trie::BinaryTrie;
+    ///
+    /// let mut trie = BinaryTrie::<4>::new();
+    /// trie.insert(1);
+    /// trie.insert(2);
+    /// trie.insert(2);
+    /// trie.insert(7);
+    /// assert_eq!(trie.count_xor_less(3, 2), 2);
+    /// assert_eq!(trie.count_xor_less(3, 5), 4);
+    /// assert_eq!(trie.count_xor_less(0, 16), 4);
+    /// ```
+    pub fn count_xor_less(&self, x: u64, k: u64) -> usize {
+        if k.checked_shr(BITS as u32).unwrap_or(0) != 0 {
+            return self.len();
+        }
+
+        let (mut node, mut ans) = (0, 0);
+        for i in (0..BITS).rev() {
+            let b = Self::bit(x, i);
+            if Self::bit(k, i) == 1 {
+                ans += self.count_at(self.nodes[node].children[b]);
+                node = self.nodes[node].children[b ^ 1];
+            } else {
+                node = self.nodes[node].children[b];
+            }
+            if node == NONE {
+                break;
+            }
+        }
+        ans
+    }
+}
+
+impl<const BITS: usize> Default for BinaryTrie<BITS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,481 @@
+use crate::math::algebra::{Magma, Monoid, Semigroup};
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, ShlAssign, Shr,
+    ShrAssign,
+};
+
+const BITS: usize = 64;
+
+/// Fixed-length sequence of bits, packed in 64-bit words
+///
+/// The length is chosen at runtime. Binary operators require both operands to have the same length,
+/// and shifts drop the bits moved out of $[0, N)$, where bit $i$ moves to $i + k$ under `<< k`.
+///
+/// # Example
+///
+/// Subset sums:
+///
+/// ```
+/// use cp_library::ds::bitset::BitSet;
+///
+/// let mut sums = BitSet::new(20);
+/// sums.set(0, true);
+/// for w in [3, 5, 7] {
+///     sums |= &(&sums << w);
+/// }
+///
+/// assert_eq!(sums.iter().collect::<Vec<_>>(), vec![0, 3, 5, 7, 8, 10, 12, 15]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    /// Creates a bitset of length `len`, with all bits unset
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::bitset::BitSet;
+    ///
+    /// let bits = BitSet::new(100);
+    /// assert_eq!(bits.len(), 100);
+    /// assert_eq!(bits.count_ones(), 0);
+    /// ```
+    pub fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(BITS)],
+            len,
+        }
+    }
+
+    /// Returns the number of bits
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns [`true`] if the bitset has length $0$
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the underlying words, bit $i$ being bit `i % 64` of word `i / 64`.
+    /// The bits past the end are always unset.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    // Clears the bits past the end of the last word
+    fn trim(&mut self) {
+        let used = self.len % BITS;
+        if let Some(last) = self.words.last_mut().filter(|_| used > 0) {
+            *last &= (1 << used) - 1;
+        }
+    }
+
+    /// Changes the length to `len`, new bits being unset
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::bitset::BitSet;
+    ///
+    /// let mut bits = BitSet::new(10);
+    /// bits.set(2, true);
+    /// bits.set(8, true);
+    /// bits.resize(5);
+    /// bits.resize(10);
+    /// assert_eq!(bits.iter().collect::<Vec<_>>(), vec![2]);
+    /// ```
+    pub fn resize(&mut self, len: usize) {
+        self.words.resize(len.div_ceil(BITS), 0);
+        self.len = len;
+        self.trim();
+    }
+
+    /// Returns the bit at `pos`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    pub fn get(&self, pos: usize) -> bool {
+        debug_assert!(pos < self.len);
+        (self.words[pos / BITS] >> (pos % BITS)) & 1 == 1
+    }
+
+    /// Sets the bit at `pos` to `val`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::bitset::BitSet;
+    ///
+    /// let mut bits = BitSet::new(100);
+    /// bits.set(70, true);
+    /// assert!(bits.get(70));
+    /// bits.set(70, false);
+    /// assert!(!bits.get(70));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::bitset::BitSet;
+    ///
+    /// let mut bits = BitSet::new(100);
+    /// bits.set(100, true);
+    /// ```
+    pub fn set(&mut self, pos: usize, val: bool) {
+        debug_assert!(pos < self.len);
+        if val {
+            self.words[pos / BITS] |= 1 << (pos % BITS);
+        } else {
+            self.words[pos / BITS] &= !(1 << (pos % BITS));
+        }
+    }
+
+    /// Flips the bit at `pos`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    pub fn flip(&mut self, pos: usize) {
+        debug_assert!(pos < self.len);
+        self.words[pos / BITS] ^= 1 << (pos % BITS);
+    }
+
+    /// Returns the number of set bits
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::bitset::BitSet;
+    ///
+    /// let bits = !BitSet::new(100);
+    /// assert_eq!(bits.count_ones(), 100);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns the position of the first set bit, or [`None`] if there is none
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    pub fn first_one(&self) -> Option<usize> {
+        self.next_one(0)
+    }
+
+    /// Returns the position of the first set bit not before `pos`, or [`None`] if there is none
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::bitset::BitSet;
+    ///
+    /// let mut bits = BitSet::new(200);
+    /// bits.set(3, true);
+    /// bits.set(150, true);
+    /// assert_eq!(bits.first_one(), Some(3));
+    /// assert_eq!(bits.next_one(3), Some(3));
+    /// assert_eq!(bits.next_one(4), Some(150));
+    /// assert_eq!(bits.next_one(151), None);
+    /// ```
+    pub fn next_one(&self, pos: usize) -> Option<usize> {
+        if pos >= self.len {
+            return None;
+        }
+
+        let rest = self.words[pos / BITS] >> (pos % BITS);
+        if rest != 0 {
+            return Some(pos + rest.trailing_zeros() as usize);
+        }
+
+        (pos / BITS + 1..self.words.len())
+            .find(|&i| self.words[i] != 0)
+            .map(|i| i * BITS + self.words[i].trailing_zeros() as usize)
+    }
+
+    /// Returns an iterator over the positions of the set bits, in increasing order
+    ///
+    /// Complexity: $\mathcal{O}(N / 64 + K)$ for the whole iteration, where:
+    /// - $K$ is the number of set bits.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &w)| {
+            std::iter::successors(Some(w), |&w| Some(w & w.wrapping_sub(1)))
+                .take_while(|&w| w != 0)
+                .map(move |w| i * BITS + w.trailing_zeros() as usize)
+        })
+    }
+}
+
+impl FromIterator<bool> for BitSet {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = BitSet::new(0);
+        for (i, b) in iter.into_iter().enumerate() {
+            if i / BITS == bits.words.len() {
+                bits.words.push(0);
+            }
+            bits.words[i / BITS] |= u64::from(b) << (i % BITS);
+            bits.len = i + 1;
+        }
+        bits
+    }
+}
+
+macro_rules! impl_bitset_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl $assign_trait<&BitSet> for BitSet {
+            fn $assign_method(&mut self, other: &BitSet) {
+                debug_assert_eq!(self.len, other.len);
+                for (a, b) in self.words.iter_mut().zip(&other.words) {
+                    *a = *a $op *b;
+                }
+            }
+        }
+
+        impl $trait<&BitSet> for BitSet {
+            type Output = BitSet;
+
+            fn $method(mut self, other: &BitSet) -> BitSet {
+                self.$assign_method(other);
+                self
+            }
+        }
+
+        impl $trait<&BitSet> for &BitSet {
+            type Output = BitSet;
+
+            fn $method(self, other: &BitSet) -> BitSet {
+                self.clone().$method(other)
+            }
+        }
+    };
+}
+
+impl_bitset_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+impl_bitset_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+impl_bitset_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl Not for BitSet {
+    type Output = BitSet;
+
+    fn not(mut self) -> BitSet {
+        for w in &mut self.words {
+            *w = !*w;
+        }
+        self.trim();
+        self
+    }
+}
+
+impl Not for &BitSet {
+    type Output = BitSet;
+
+    fn not(self) -> BitSet {
+        !self.clone()
+    }
+}
+
+impl ShlAssign<usize> for BitSet {
+    /// Moves bit $i$ to $i + k$, dropping the bits moved past the end
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    fn shl_assign(&mut self, k: usize) {
+        let (ws, bs) = (k / BITS, k % BITS);
+        let n = self.words.len();
+        for i in (0..n).rev() {
+            self.words[i] = match i.checked_sub(ws) {
+                None => 0,
+                Some(j) if bs == 0 || j == 0 => self.words[j] << bs,
+                Some(j) => (self.words[j] << bs) | (self.words[j - 1] >> (BITS - bs)),
+            };
+        }
+        self.trim();
+    }
+}
+
+impl ShrAssign<usize> for BitSet {
+    /// Moves bit $i$ to $i - k$, dropping the bits moved before the start
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    fn shr_assign(&mut self, k: usize) {
+        let (ws, bs) = (k / BITS, k % BITS);
+        let n = self.words.len();
+        for i in 0..n {
+            let j = i.saturating_add(ws);
+            self.words[i] = if j >= n {
+                0
+            } else if bs == 0 || j + 1 == n {
+                self.words[j] >> bs
+            } else {
+                (self.words[j] >> bs) | (self.words[j + 1] << (BITS - bs))
+            };
+        }
+    }
+}
+
+impl Shl<usize> for BitSet {
+    type Output = BitSet;
+
+    fn shl(mut self, k: usize) -> BitSet {
+        self <<= k;
+        self
+    }
+}
+
+impl Shl<usize> for &BitSet {
+    type Output = BitSet;
+
+    fn shl(self, k: usize) -> BitSet {
+        self.clone() << k
+    }
+}
+
+impl Shr<usize> for BitSet {
+    type Output = BitSet;
+
+    fn shr(mut self, k: usize) -> BitSet {
+        self >>= k;
+        self
+    }
+}
+
+impl Shr<usize> for &BitSet {
+    type Output = BitSet;
+
+    fn shr(self, k: usize) -> BitSet {
+        self.clone() >> k
+    }
+}
+
+/// [`BitSet`] under union, as a [`Monoid`]
+///
+/// The neutral element is the empty bitset, and shorter operands are padded with unset bits.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::bitset::{BitSet, Union};
+/// use cp_library::ds::segtree::SegTree;
+///
+/// let sets: Vec<_> = [0b001, 0b100, 0b110]
+///     .iter()
+///     .map(|&m| Union((0..3).map(|i| (m >> i) & 1 == 1).collect()))
+///     .collect();
+///
+/// let tree = SegTree::from(&sets);
+/// assert_eq!(tree.query(0, 2).0.iter().collect::<Vec<_>>(), vec![0, 2]);
+/// assert_eq!(tree.query(1, 3).0.iter().collect::<Vec<_>>(), vec![1, 2]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Union(pub BitSet);
+
+/// [`BitSet`] under symmetric difference, as a [`Monoid`]
+///
+/// The neutral element is the empty bitset, and shorter operands are padded with unset bits.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Xor(pub BitSet);
+
+/// [`BitSet`] under intersection, as a [`Semigroup`]
+///
+/// Since the length is dynamic there is no neutral element: wrap it in an [`Option`] to get a [`Monoid`].
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::bitset::{BitSet, Intersection};
+/// use cp_library::ds::segtree::SegTree;
+///
+/// let sets: Vec<_> = [0b011, 0b111, 0b110]
+///     .iter()
+///     .map(|&m| Some(Intersection((0..3).map(|i| (m >> i) & 1 == 1).collect())))
+///     .collect();
+///
+/// let tree = SegTree::from(&sets);
+/// assert_eq!(tree.query(0, 3).unwrap().0.iter().collect::<Vec<_>>(), vec![1]);
+/// assert_eq!(tree.query(1, 3).unwrap().0.iter().collect::<Vec<_>>(), vec![1, 2]);
+/// assert_eq!(tree.query(1, 1), None);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Intersection(pub BitSet);
+
+impl Magma for Union {
+    fn op(self, other: Self) -> Self {
+        let (mut long, short) = if self.0.len >= other.0.len {
+            (self.0, other.0)
+        } else {
+            (other.0, self.0)
+        };
+        for (a, b) in long.words.iter_mut().zip(&short.words) {
+            *a |= b;
+        }
+        Union(long)
+    }
+}
+
+impl Semigroup for Union {}
+
+impl Monoid for Union {
+    const ID: Self = Union(BitSet {
+        words: Vec::new(),
+        len: 0,
+    });
+}
+
+impl Magma for Xor {
+    fn op(self, other: Self) -> Self {
+        let (mut long, short) = if self.0.len >= other.0.len {
+            (self.0, other.0)
+        } else {
+            (other.0, self.0)
+        };
+        for (a, b) in long.words.iter_mut().zip(&short.words) {
+            *a ^= b;
+        }
+        Xor(long)
+    }
+}
+
+impl Semigroup for Xor {}
+
+impl Monoid for Xor {
+    const ID: Self = Xor(BitSet {
+        words: Vec::new(),
+        len: 0,
+    });
+}
+
+impl Magma for Intersection {
+    /// # Panics
+    ///
+    /// Only in debug builds, if the operands have different lengths.
+    fn op(self, other: Self) -> Self {
+        Intersection(self.0 & &other.0)
+    }
+}
+
+impl Semigroup for Intersection {}
@@ -1,6 +1,7 @@
 use crate::ds::sorted_vec::SortedVec;
 
 /// A `SortedVec` wrapper able to provide coordinate compression
+#[derive(Clone, Debug)]
 pub struct CoordinateCompressor<T> {
     coords: SortedVec<T>,
 }
@@ -72,6 +73,28 @@ where
         self.coords.lower_bound(coord)
     }
 
+    /// Returns the compressed value of the first stored coordinate not smaller than `coord`,
+    /// or the number of stored coordinates if none exists.
+    ///
+    /// Unlike [`CoordinateCompressor::compress`], `coord` need not be present in the compressor.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ comparisons where
+    /// - $N$ is the number of coordinates.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::coord_comp::CoordinateCompressor;
+    ///
+    /// let comp = CoordinateCompressor::from_coords(vec![100, 200, 300]);
+    /// assert_eq!(comp.lower_bound(&200), 1);
+    /// assert_eq!(comp.lower_bound(&201), 2);
+    /// assert_eq!(comp.lower_bound(&301), 3);
+    /// ```
+    pub fn lower_bound(&self, coord: &T) -> usize {
+        self.coords.lower_bound(coord)
+    }
+
     /// Decompresses a given coordinate
     ///
     /// Complexity: $\mathcal{O}(1)$
@@ -0,0 +1,538 @@
+use crate::math::algebra::Group;
+use std::mem;
+
+/// Disjoint set union with path compression and union by size
+#[derive(Clone, Debug)]
+pub struct Dsu {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl Dsu {
+    /// Builds a disjoint set union of `size` singletons
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let dsu = Dsu::new(10);
+    /// assert_eq!(dsu.components(), 10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        Dsu {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            components: size,
+        }
+    }
+
+    /// Finds the representative of the set containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let mut dsu = Dsu::new(10);
+    /// dsu.unite(2, 3);
+    /// assert_eq!(dsu.find(2), dsu.find(3));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid element.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let mut dsu = Dsu::new(10);
+    /// dsu.find(10);
+    /// ```
+    pub fn find(&mut self, v: usize) -> usize {
+        let mut root = v;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut v = v;
+        while self.parent[v] != root {
+            v = mem::replace(&mut self.parent[v], root);
+        }
+
+        root
+    }
+
+    /// Merges the sets containing `u` and `v`.
+    /// Returns [`false`] if they were already in the same set.
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let mut dsu = Dsu::new(10);
+    /// assert!(dsu.unite(2, 3));
+    /// assert!(dsu.unite(3, 4));
+    /// assert!(!dsu.unite(2, 4));
+    /// ```
+    pub fn unite(&mut self, u: usize, v: usize) -> bool {
+        let (mut u, mut v) = (self.find(u), self.find(v));
+        if u == v {
+            return false;
+        }
+
+        if self.size[u] < self.size[v] {
+            mem::swap(&mut u, &mut v);
+        }
+        self.parent[v] = u;
+        self.size[u] += self.size[v];
+        self.components -= 1;
+
+        true
+    }
+
+    /// Checks whether `u` and `v` are in the same set
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let mut dsu = Dsu::new(10);
+    /// dsu.unite(2, 3);
+    /// assert!(dsu.same(2, 3));
+    /// assert!(!dsu.same(2, 4));
+    /// ```
+    pub fn same(&mut self, u: usize, v: usize) -> bool {
+        self.find(u) == self.find(v)
+    }
+
+    /// Returns the size of the set containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let mut dsu = Dsu::new(10);
+    /// dsu.unite(2, 3);
+    /// dsu.unite(4, 3);
+    /// assert_eq!(dsu.size(2), 3);
+    /// assert_eq!(dsu.size(5), 1);
+    /// ```
+    pub fn size(&mut self, v: usize) -> usize {
+        let root = self.find(v);
+        self.size[root]
+    }
+
+    /// Returns the number of disjoint sets
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let mut dsu = Dsu::new(10);
+    /// dsu.unite(2, 3);
+    /// assert_eq!(dsu.components(), 9);
+    /// ```
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Returns the list of sets, each sorted by element.
+    /// Sets are sorted by their smallest element.
+    ///
+    /// Complexity: $\mathcal{O}(N \alpha(N))$ where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::Dsu;
+    ///
+    /// let mut dsu = Dsu::new(5);
+    /// dsu.unite(0, 3);
+    /// dsu.unite(4, 1);
+    /// assert_eq!(dsu.groups(), vec![vec![0, 3], vec![1, 4], vec![2]]);
+    /// ```
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let n = self.parent.len();
+        let mut index = vec![usize::MAX; n];
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+
+        for v in 0..n {
+            let root = self.find(v);
+            if index[root] == usize::MAX {
+                index[root] = groups.len();
+                groups.push(Vec::with_capacity(self.size[root]));
+            }
+            groups[index[root]].push(v);
+        }
+
+        groups
+    }
+}
+
+/// Disjoint set union supporting rollback to a previous state
+///
+/// Uses union by size without path compression, so that every merge can be undone.
+#[derive(Clone, Debug)]
+pub struct RollbackDsu {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+    history: Vec<(usize, usize)>,
+}
+
+impl RollbackDsu {
+    /// Builds a disjoint set union of `size` singletons
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::RollbackDsu;
+    ///
+    /// let dsu = RollbackDsu::new(10);
+    /// assert_eq!(dsu.components(), 10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        RollbackDsu {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            components: size,
+            history: vec![],
+        }
+    }
+
+    /// Finds the representative of the set containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::RollbackDsu;
+    ///
+    /// let mut dsu = RollbackDsu::new(10);
+    /// dsu.unite(2, 3);
+    /// assert_eq!(dsu.find(2), dsu.find(3));
+    /// ```
+    pub fn find(&self, mut v: usize) -> usize {
+        while self.parent[v] != v {
+            v = self.parent[v];
+        }
+        v
+    }
+
+    /// Merges the sets containing `u` and `v`.
+    /// Returns [`false`] if they were already in the same set.
+    ///
+    /// A merge is recorded in the history even if it fails,
+    /// so that every call to `unite` can be undone by [`RollbackDsu::undo`].
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::RollbackDsu;
+    ///
+    /// let mut dsu = RollbackDsu::new(10);
+    /// assert!(dsu.unite(2, 3));
+    /// assert!(!dsu.unite(3, 2));
+    /// ```
+    pub fn unite(&mut self, u: usize, v: usize) -> bool {
+        let (mut u, mut v) = (self.find(u), self.find(v));
+        if u == v {
+            self.history.push((u, u));
+            return false;
+        }
+
+        if self.size[u] < self.size[v] {
+            mem::swap(&mut u, &mut v);
+        }
+        self.parent[v] = u;
+        self.size[u] += self.size[v];
+        self.components -= 1;
+        self.history.push((u, v));
+
+        true
+    }
+
+    /// Checks whether `u` and `v` are in the same set
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements.
+    pub fn same(&self, u: usize, v: usize) -> bool {
+        self.find(u) == self.find(v)
+    }
+
+    /// Returns the size of the set containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements.
+    pub fn size(&self, v: usize) -> usize {
+        self.size[self.find(v)]
+    }
+
+    /// Returns the number of disjoint sets
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Returns a handle to the current state, to be passed to [`RollbackDsu::rollback`]
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::RollbackDsu;
+    ///
+    /// let mut dsu = RollbackDsu::new(10);
+    /// dsu.unite(1, 2);
+    ///
+    /// let snap = dsu.snapshot();
+    /// dsu.unite(2, 3);
+    /// dsu.unite(4, 5);
+    /// assert!(dsu.same(1, 3));
+    ///
+    /// dsu.rollback(snap);
+    /// assert!(!dsu.same(1, 3));
+    /// assert!(!dsu.same(4, 5));
+    /// assert!(dsu.same(1, 2));
+    /// ```
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes the last call to [`RollbackDsu::unite`]
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// If there is nothing to undo.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::dsu::RollbackDsu;
+    ///
+    /// let mut dsu = RollbackDsu::new(10);
+    /// dsu.undo();
+    /// ```
+    pub fn undo(&mut self) {
+        let (u, v) = self.history.pop().unwrap();
+        if u != v {
+            self.parent[v] = v;
+            self.size[u] -= self.size[v];
+            self.components += 1;
+        }
+    }
+
+    /// Rolls back to the state identified by `snapshot`
+    ///
+    /// Complexity: $\mathcal{O}(K)$ where:
+    /// - $K$ is the number of merges made after the snapshot.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if the state was already rolled back past `snapshot`.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::dsu::RollbackDsu;
+    ///
+    /// let mut dsu = RollbackDsu::new(10);
+    /// dsu.unite(1, 2);
+    /// let snap = dsu.snapshot();
+    /// dsu.undo();
+    /// dsu.rollback(snap);
+    /// ```
+    pub fn rollback(&mut self, snapshot: usize) {
+        debug_assert!(snapshot <= self.history.len());
+        while self.history.len() > snapshot {
+            self.undo();
+        }
+    }
+}
+
+/// Disjoint set union maintaining potentials over a [group](https://en.wikipedia.org/wiki/Group_(mathematics)) `G`
+///
+/// Every element $v$ has an unknown potential $p(v)$; constraints of the form
+/// $p(v) = p(u) \cdot w$ can be added and the difference $p(u)^{-1} \cdot p(v)$
+/// can be queried for elements in the same set.
+/// `G` need not be commutative.
+///
+/// `G` must be [`Clone`], [`PartialEq`] and [`Group`]
+#[derive(Clone, Debug)]
+pub struct PotentialDsu<G> {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    diff: Vec<G>,
+}
+
+impl<G> PotentialDsu<G>
+where
+    G: Clone + PartialEq + Group,
+{
+    /// Builds a disjoint set union of `size` singletons
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::PotentialDsu;
+    ///
+    /// let dsu: PotentialDsu<i64> = PotentialDsu::new(10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        PotentialDsu {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            diff: vec![G::ID; size],
+        }
+    }
+
+    /// Finds the representative $r$ of the set containing `v`,
+    /// together with $p(r)^{-1} \cdot p(v)$.
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized group operations where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::PotentialDsu;
+    ///
+    /// let mut dsu = PotentialDsu::new(10);
+    /// dsu.unite(2, 3, 5);
+    /// let (root, pot) = dsu.find(3);
+    /// assert_eq!(dsu.find(2), (root, pot - 5));
+    /// ```
+    pub fn find(&mut self, v: usize) -> (usize, G) {
+        let mut root = v;
+        let mut pot = G::ID;
+        while self.parent[root] != root {
+            pot = self.diff[root].clone().op(pot);
+            root = self.parent[root];
+        }
+
+        let (mut u, mut cur) = (v, pot.clone());
+        while u != root && self.parent[u] != root {
+            let next = mem::replace(&mut self.parent[u], root);
+            let next_pot = cur.clone().op(self.diff[u].clone().inv());
+            self.diff[u] = cur;
+            (u, cur) = (next, next_pot);
+        }
+
+        (root, pot)
+    }
+
+    /// Adds the constraint $p(v) = p(u) \cdot w$, merging the sets of `u` and `v`.
+    /// Returns [`false`] if the constraint contradicts the previous ones,
+    /// in which case nothing is changed.
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized group operations where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::PotentialDsu;
+    ///
+    /// let mut dsu = PotentialDsu::new(10);
+    /// assert!(dsu.unite(1, 2, 3));
+    /// assert!(dsu.unite(2, 3, 4));
+    /// assert!(dsu.unite(1, 3, 7));
+    /// assert!(!dsu.unite(3, 1, 7));
+    /// ```
+    pub fn unite(&mut self, u: usize, v: usize, w: G) -> bool {
+        let (ru, du) = self.find(u);
+        let (rv, dv) = self.find(v);
+
+        if ru == rv {
+            return du.op(w) == dv;
+        }
+
+        if self.size[ru] >= self.size[rv] {
+            self.parent[rv] = ru;
+            self.size[ru] += self.size[rv];
+            self.diff[rv] = du.op(w).op(dv.inv());
+        } else {
+            self.parent[ru] = rv;
+            self.size[rv] += self.size[ru];
+            self.diff[ru] = dv.op(w.inv()).op(du.inv());
+        }
+
+        true
+    }
+
+    /// Returns $p(u)^{-1} \cdot p(v)$, or [`None`] if it is not determined
+    /// (`u` and `v` are in different sets).
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized group operations where:
+    /// - $N$ is the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dsu::PotentialDsu;
+    ///
+    /// let mut dsu = PotentialDsu::new(10);
+    /// dsu.unite(1, 2, 3);
+    /// dsu.unite(3, 2, 4);
+    /// assert_eq!(dsu.diff(1, 3), Some(-1));
+    /// assert_eq!(dsu.diff(3, 1), Some(1));
+    /// assert_eq!(dsu.diff(1, 4), None);
+    /// ```
+    pub fn diff(&mut self, u: usize, v: usize) -> Option<G> {
+        let (ru, du) = self.find(u);
+        let (rv, dv) = self.find(v);
+        (ru == rv).then(|| du.inv().op(dv))
+    }
+
+    /// Checks whether `u` and `v` are in the same set
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized group operations where:
+    /// - $N$ is the number of elements.
+    pub fn same(&mut self, u: usize, v: usize) -> bool {
+        self.find(u).0 == self.find(v).0
+    }
+
+    /// Returns the size of the set containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\alpha(N))$ amortized group operations where:
+    /// - $N$ is the number of elements.
+    pub fn size(&mut self, v: usize) -> usize {
+        let root = self.find(v).0;
+        self.size[root]
+    }
+}
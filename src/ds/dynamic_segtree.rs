@@ -0,0 +1,594 @@
+use crate::math::algebra::{Action, Monoid};
+
+const NONE: usize = usize::MAX;
+
+// Rounds down, and does not overflow even if `r - l` does not fit in an `i64`
+fn midpoint(l: i64, r: i64) -> i64 {
+    (l & r) + ((l ^ r) >> 1)
+}
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    val: T,
+    l: usize,
+    r: usize,
+}
+
+/// Segment tree over a [monoid](https://en.wikipedia.org/wiki/Monoid) `T`,
+/// indexed by a range of `i64` and allocating nodes only when they are updated
+///
+/// Positions never updated hold the identity element. Nodes are stored in a single arena.
+///
+/// `T` must be [`Clone`] and [`Monoid`]
+#[derive(Clone, Debug)]
+pub struct DynamicSegTree<T> {
+    nodes: Vec<Node<T>>,
+    lo: i64,
+    hi: i64,
+}
+
+impl<T> DynamicSegTree<T>
+where
+    T: Clone + Monoid,
+{
+    /// Builds a segment tree over the range $[lo, hi)$, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let x: DynamicSegTree<i64> = DynamicSegTree::new(-1_000_000_000_000_000_000, 1_000_000_000_000_000_000);
+    ///
+    /// let mut y = DynamicSegTree::new(i64::MIN, i64::MAX);
+    /// y.update(i64::MIN, &1);
+    /// y.update(i64::MAX - 1, &2);
+    /// assert_eq!(y.query(i64::MIN, i64::MAX), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if the range is empty.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let x: DynamicSegTree<i64> = DynamicSegTree::new(3, 3);
+    /// ```
+    pub fn new(lo: i64, hi: i64) -> Self {
+        Self::with_capacity(lo, hi, 1)
+    }
+
+    /// Builds a segment tree over the range $[lo, hi)$, filled with identity elements,
+    /// with space reserved for `nodes` nodes.
+    ///
+    /// Each update creates at most $\lceil \log_2 (hi - lo) \rceil + 1$ nodes.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let x: DynamicSegTree<i64> = DynamicSegTree::with_capacity(0, 1 << 60, 200_000 * 61);
+    /// ```
+    pub fn with_capacity(lo: i64, hi: i64, nodes: usize) -> Self {
+        debug_assert!(lo < hi);
+
+        let mut arena = Vec::with_capacity(nodes);
+        arena.push(Node {
+            val: T::ID,
+            l: NONE,
+            r: NONE,
+        });
+
+        DynamicSegTree {
+            nodes: arena,
+            lo,
+            hi,
+        }
+    }
+
+    /// Reserves space for at least `additional` more nodes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let mut x: DynamicSegTree<i64> = DynamicSegTree::new(0, 1 << 60);
+    /// x.reserve(1000);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    /// Returns the number of allocated nodes
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let mut x = DynamicSegTree::new(0, 8);
+    /// x.update(5, &1);
+    /// assert_eq!(x.node_count(), 4);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn child(&mut self, node: usize, right: bool) -> usize {
+        let old = if right {
+            self.nodes[node].r
+        } else {
+            self.nodes[node].l
+        };
+        if old != NONE {
+            return old;
+        }
+
+        self.nodes.push(Node {
+            val: T::ID,
+            l: NONE,
+            r: NONE,
+        });
+        let new = self.nodes.len() - 1;
+        if right {
+            self.nodes[node].r = new;
+        } else {
+            self.nodes[node].l = new;
+        }
+        new
+    }
+
+    fn val(&self, node: usize) -> T {
+        if node == NONE {
+            T::ID
+        } else {
+            self.nodes[node].val.clone()
+        }
+    }
+
+    /// Updates the element at `pos` with value `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations where:
+    /// - $N$ is the length of the range of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let mut x = DynamicSegTree::new(0, 1 << 60);
+    /// x.update(1 << 59, &4);
+    /// x.update(3, &2);
+    /// assert_eq!(x.query(0, 1 << 60), 6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let mut x = DynamicSegTree::new(-5, 5);
+    /// x.update(5, &4);
+    /// ```
+    pub fn update(&mut self, pos: i64, val: &T) {
+        debug_assert!(self.lo <= pos && pos < self.hi);
+        self.update_rec(0, self.lo, self.hi, pos, val);
+    }
+
+    fn update_rec(&mut self, node: usize, nl: i64, nr: i64, pos: i64, val: &T) {
+        if nl + 1 == nr {
+            self.nodes[node].val = val.clone();
+            return;
+        }
+
+        let mid = midpoint(nl, nr);
+        if pos < mid {
+            let l = self.child(node, false);
+            self.update_rec(l, nl, mid, pos, val);
+        } else {
+            let r = self.child(node, true);
+            self.update_rec(r, mid, nr, pos, val);
+        }
+
+        let Node { l, r, .. } = self.nodes[node];
+        self.nodes[node].val = self.val(l).op(self.val(r));
+    }
+
+    /// Returns the element at `pos`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the length of the range of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let mut x = DynamicSegTree::new(-5, 5);
+    /// x.update(-3, &4);
+    /// assert_eq!(x.get(-3), 4);
+    /// assert_eq!(x.get(2), 0);
+    /// ```
+    pub fn get(&self, pos: i64) -> T {
+        debug_assert!(self.lo <= pos && pos < self.hi);
+
+        let (mut node, mut nl, mut nr) = (0, self.lo, self.hi);
+        while nl + 1 < nr && node != NONE {
+            let mid = midpoint(nl, nr);
+            if pos < mid {
+                (node, nr) = (self.nodes[node].l, mid);
+            } else {
+                (node, nl) = (self.nodes[node].r, mid);
+            }
+        }
+
+        if nl + 1 == nr {
+            self.val(node)
+        } else {
+            T::ID
+        }
+    }
+
+    /// Perform a range query on the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations where:
+    /// - $N$ is the length of the range of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let mut x = DynamicSegTree::new(-1_000_000_000, 1_000_000_000);
+    /// x.update(-500, &1);
+    /// x.update(0, &2);
+    /// x.update(500, &3);
+    /// assert_eq!(x.query(-500, 500), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range:
+    /// ```should_panic
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let x: DynamicSegTree<i64> = DynamicSegTree::new(0, 10);
+    /// let y = x.query(3, 2);
+    /// ```
+    pub fn query(&self, l: i64, r: i64) -> T {
+        debug_assert!(self.lo <= l && l <= r && r <= self.hi);
+        self.query_rec(0, self.lo, self.hi, l, r)
+    }
+
+    fn query_rec(&self, node: usize, nl: i64, nr: i64, l: i64, r: i64) -> T {
+        if r <= nl || nr <= l || node == NONE {
+            return T::ID;
+        }
+        if l <= nl && nr <= r {
+            return self.nodes[node].val.clone();
+        }
+
+        let mid = midpoint(nl, nr);
+        let Node { l: cl, r: cr, .. } = self.nodes[node];
+        self.query_rec(cl, nl, mid, l, r)
+            .op(self.query_rec(cr, mid, nr, l, r))
+    }
+
+    /// Finds the largest $r$ such that `pred` returns [`true`] on the result of the query on $[l, r)$.
+    ///
+    /// Conditions: `pred` must return [`true`] on the identity element,
+    /// and be monotone (if it returns [`false`] on $[l, r)$, it must return [`false`] on all larger ranges).
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations and calls to `pred` where:
+    /// - $N$ is the length of the range of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicSegTree;
+    ///
+    /// let mut x = DynamicSegTree::new(0, 1 << 40);
+    /// x.update(10, &3);
+    /// x.update(1 << 30, &4);
+    /// x.update(1 << 35, &5);
+    ///
+    /// assert_eq!(x.max_right(0, |s| s <= 7), 1 << 35);
+    /// assert_eq!(x.max_right(1 << 31, |s| s <= 7), 1 << 40);
+    /// assert_eq!(x.max_right(0, |s| s < 3), 10);
+    /// ```
+    pub fn max_right<P>(&self, l: i64, pred: P) -> i64
+    where
+        P: Fn(T) -> bool,
+    {
+        debug_assert!(self.lo <= l && l <= self.hi);
+
+        let mut acc = T::ID;
+        self.max_right_rec(0, self.lo, self.hi, l, &pred, &mut acc)
+            .unwrap_or(self.hi)
+    }
+
+    fn max_right_rec<P>(
+        &self,
+        node: usize,
+        nl: i64,
+        nr: i64,
+        l: i64,
+        pred: &P,
+        acc: &mut T,
+    ) -> Option<i64>
+    where
+        P: Fn(T) -> bool,
+    {
+        if nr <= l || node == NONE {
+            return None;
+        }
+        if l <= nl {
+            let next = acc.clone().op(self.nodes[node].val.clone());
+            if pred(next.clone()) {
+                *acc = next;
+                return None;
+            }
+            if nl + 1 == nr {
+                return Some(nl);
+            }
+        }
+
+        let mid = midpoint(nl, nr);
+        let Node { l: cl, r: cr, .. } = self.nodes[node];
+        self.max_right_rec(cl, nl, mid, l, pred, acc)
+            .or_else(|| self.max_right_rec(cr, mid, nr, l, pred, acc))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct LazyNode<T, F> {
+    val: T,
+    lazy: F,
+    l: usize,
+    r: usize,
+}
+
+/// Segment tree over a [monoid](https://en.wikipedia.org/wiki/Monoid) `T`
+/// with range updates by an [action](crate::math::algebra::Action) `F`,
+/// indexed by a range of `i64` and allocating nodes only when they are visited
+///
+/// The value of a range never visited is given by a user provided function,
+/// so that it can carry information such as its length.
+///
+/// `T` must be [`Clone`] and [`Monoid`], `F` must be [`Clone`] and [`Action<T>`]
+#[derive(Clone, Debug)]
+pub struct DynamicLazySegTree<T, F> {
+    nodes: Vec<LazyNode<T, F>>,
+    init: fn(i64, i64) -> T,
+    lo: i64,
+    hi: i64,
+}
+
+impl<T, F> DynamicLazySegTree<T, F>
+where
+    T: Clone + Monoid,
+    F: Clone + Action<T>,
+{
+    /// Builds a segment tree over the range $[lo, hi)$,
+    /// where the value of each range $[l, r)$ is initially `init(l, r)`.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicLazySegTree;
+    /// use cp_library::math::algebra::instances::{Affine, SumLen};
+    ///
+    /// let x: DynamicLazySegTree<_, Affine<i64>> = DynamicLazySegTree::new(0, 1 << 60, |l, r| SumLen { sum: 0, len: r - l });
+    /// ```
+    pub fn new(lo: i64, hi: i64, init: fn(i64, i64) -> T) -> Self {
+        Self::with_capacity(lo, hi, init, 1)
+    }
+
+    /// Builds a segment tree over the range $[lo, hi)$,
+    /// where the value of each range $[l, r)$ is initially `init(l, r)`,
+    /// with space reserved for `nodes` nodes.
+    ///
+    /// Each operation creates at most $4 \lceil \log_2 (hi - lo) \rceil$ nodes.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn with_capacity(lo: i64, hi: i64, init: fn(i64, i64) -> T, nodes: usize) -> Self {
+        debug_assert!(lo < hi);
+
+        let mut arena = Vec::with_capacity(nodes);
+        arena.push(LazyNode {
+            val: init(lo, hi),
+            lazy: F::ID,
+            l: NONE,
+            r: NONE,
+        });
+
+        DynamicLazySegTree {
+            nodes: arena,
+            init,
+            lo,
+            hi,
+        }
+    }
+
+    /// Reserves space for at least `additional` more nodes
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    /// Returns the number of allocated nodes
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn new_node(&mut self, l: i64, r: i64) -> usize {
+        self.nodes.push(LazyNode {
+            val: (self.init)(l, r),
+            lazy: F::ID,
+            l: NONE,
+            r: NONE,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn apply_node(&mut self, node: usize, f: &F) {
+        let node = &mut self.nodes[node];
+        node.val = f.clone().act(node.val.clone());
+        node.lazy = node.lazy.clone().op(f.clone());
+    }
+
+    fn push(&mut self, node: usize, nl: i64, nr: i64) -> (usize, usize) {
+        let mid = midpoint(nl, nr);
+        if self.nodes[node].l == NONE {
+            let l = self.new_node(nl, mid);
+            self.nodes[node].l = l;
+        }
+        if self.nodes[node].r == NONE {
+            let r = self.new_node(mid, nr);
+            self.nodes[node].r = r;
+        }
+
+        let LazyNode { l, r, .. } = self.nodes[node];
+        let lazy = std::mem::replace(&mut self.nodes[node].lazy, F::ID);
+        self.apply_node(l, &lazy);
+        self.apply_node(r, &lazy);
+
+        (l, r)
+    }
+
+    /// Applies `f` to all the elements in the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations and actions where:
+    /// - $N$ is the length of the range of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicLazySegTree;
+    /// use cp_library::math::algebra::instances::{Affine, SumLen};
+    ///
+    /// let mut x = DynamicLazySegTree::new(0, 1_000_000_000, |l, r| SumLen { sum: 0, len: r - l });
+    /// x.apply(0, 500_000_000, Affine::new(1, 2));
+    /// x.apply(250_000_000, 750_000_000, Affine::new(3, 0));
+    ///
+    /// assert_eq!(x.query(0, 1_000_000_000).sum, 2_000_000_000);
+    /// assert_eq!(x.query(249_999_999, 250_000_001).sum, 8);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn apply(&mut self, l: i64, r: i64, f: F) {
+        debug_assert!(self.lo <= l && l <= r && r <= self.hi);
+        self.apply_rec(0, self.lo, self.hi, l, r, &f);
+    }
+
+    fn apply_rec(&mut self, node: usize, nl: i64, nr: i64, l: i64, r: i64, f: &F) {
+        if r <= nl || nr <= l {
+            return;
+        }
+        if l <= nl && nr <= r {
+            self.apply_node(node, f);
+            return;
+        }
+
+        let mid = midpoint(nl, nr);
+        let (cl, cr) = self.push(node, nl, nr);
+        self.apply_rec(cl, nl, mid, l, r, f);
+        self.apply_rec(cr, mid, nr, l, r, f);
+        self.nodes[node].val = self.nodes[cl].val.clone().op(self.nodes[cr].val.clone());
+    }
+
+    /// Updates the element at `pos` with value `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations and actions where:
+    /// - $N$ is the length of the range of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicLazySegTree;
+    /// use cp_library::math::algebra::instances::{Affine, SumLen};
+    ///
+    /// let mut x = DynamicLazySegTree::new(0, 100, |l, r| SumLen { sum: 0, len: r - l });
+    /// x.apply(0, 100, Affine::new(1, 1));
+    /// x.update(50, &SumLen { sum: 10, len: 1 });
+    /// assert_eq!(x.query(0, 100).sum, 109);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    pub fn update(&mut self, pos: i64, val: &T) {
+        debug_assert!(self.lo <= pos && pos < self.hi);
+        self.update_rec(0, self.lo, self.hi, pos, val);
+    }
+
+    fn update_rec(&mut self, node: usize, nl: i64, nr: i64, pos: i64, val: &T) {
+        if nl + 1 == nr {
+            self.nodes[node].val = val.clone();
+            return;
+        }
+
+        let mid = midpoint(nl, nr);
+        let (cl, cr) = self.push(node, nl, nr);
+        if pos < mid {
+            self.update_rec(cl, nl, mid, pos, val);
+        } else {
+            self.update_rec(cr, mid, nr, pos, val);
+        }
+        self.nodes[node].val = self.nodes[cl].val.clone().op(self.nodes[cr].val.clone());
+    }
+
+    /// Perform a range query on the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations and actions where:
+    /// - $N$ is the length of the range of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::dynamic_segtree::DynamicLazySegTree;
+    /// use cp_library::math::algebra::instances::{Affine, SumLen};
+    ///
+    /// let mut x = DynamicLazySegTree::new(-50, 50, |l, r| SumLen { sum: 0, len: r - l });
+    /// x.apply(-10, 10, Affine::new(1, 3));
+    /// assert_eq!(x.query(-20, 0), SumLen { sum: 30, len: 20 });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn query(&mut self, l: i64, r: i64) -> T {
+        debug_assert!(self.lo <= l && l <= r && r <= self.hi);
+        self.query_rec(0, self.lo, self.hi, l, r)
+    }
+
+    fn query_rec(&mut self, node: usize, nl: i64, nr: i64, l: i64, r: i64) -> T {
+        if r <= nl || nr <= l {
+            return T::ID;
+        }
+        if l <= nl && nr <= r {
+            return self.nodes[node].val.clone();
+        }
+
+        let mid = midpoint(nl, nr);
+        let (cl, cr) = self.push(node, nl, nr);
+        self.query_rec(cl, nl, mid, l, r)
+            .op(self.query_rec(cr, mid, nr, l, r))
+    }
+}
@@ -0,0 +1,734 @@
+use crate::ds::treap::xorshift;
+use crate::math::algebra::Monoid;
+use std::collections::{HashMap, HashSet};
+
+const NONE: usize = usize::MAX;
+const SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+// Kinds of flagged nodes, used by DynamicConnectivity
+const VERTEX: usize = 0;
+const EDGE: usize = 1;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    val: T,
+    sum: T,
+    size: usize,
+    vertices: usize,
+    // The directed edge represented by the node, (v, v) for the node of vertex v
+    ends: (usize, usize),
+    flag: bool,
+    // Whether the subtree contains a flagged vertex node and a flagged edge node
+    flagged: [bool; 2],
+    priority: u64,
+    l: usize,
+    r: usize,
+    p: usize,
+}
+
+/// Euler tour tree over a commutative [monoid](https://en.wikipedia.org/wiki/Monoid) `T`
+///
+/// `T` must be [`Clone`] and [`Monoid`], and should be commutative since tours are rotated freely.
+///
+/// Maintains a forest on vertices $[0, N)$ with a value on each vertex, under edge insertions and deletions.
+/// Each tree is stored as its Euler tour in a treap, with a node for each vertex and for each directed edge,
+/// so that whole trees and subtrees are contiguous ranges of a tour.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::euler_tour_tree::EulerTourTree;
+///
+/// let mut ett = EulerTourTree::from(&[1, 2, 3, 4, 5]);
+/// ett.link(0, 1);
+/// ett.link(1, 2);
+/// ett.link(1, 3);
+/// assert_eq!(ett.component_fold(2), 10);
+/// assert_eq!(ett.subtree_fold(1, 0), Some(9));
+///
+/// ett.cut(1, 0);
+/// ett.link(0, 4);
+/// assert_eq!(ett.component_fold(0), 6);
+/// assert!(!ett.connected(0, 3));
+/// ```
+#[derive(Clone, Debug)]
+pub struct EulerTourTree<T> {
+    nodes: Vec<Node<T>>,
+    edges: HashMap<(usize, usize), usize>,
+    free: Vec<usize>,
+    size: usize,
+    seed: u64,
+}
+
+impl<T> EulerTourTree<T>
+where
+    T: Clone + Monoid,
+{
+    /// Builds a forest of `size` isolated vertices, with identity values
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::euler_tour_tree::EulerTourTree;
+    ///
+    /// let ett: EulerTourTree<i64> = EulerTourTree::new(10);
+    /// assert_eq!(ett.len(), 10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        Self::from(&vec![T::ID; size])
+    }
+
+    /// Builds a forest of isolated vertices with the given values
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::euler_tour_tree::EulerTourTree;
+    ///
+    /// let ett = EulerTourTree::from(&[1, 2, 3]);
+    /// assert_eq!(ett.get(1), 2);
+    /// ```
+    pub fn from(values: &[T]) -> Self {
+        let mut ett = EulerTourTree {
+            nodes: Vec::with_capacity(3 * values.len()),
+            edges: HashMap::new(),
+            free: vec![],
+            size: values.len(),
+            seed: SEED,
+        };
+        for (v, val) in values.iter().enumerate() {
+            ett.new_node((v, v), val.clone());
+        }
+        ett
+    }
+
+    /// Returns the number of vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns [`true`] if the forest has no vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn new_node(&mut self, ends: (usize, usize), val: T) -> usize {
+        let node = Node {
+            sum: val.clone(),
+            val,
+            size: 1,
+            vertices: usize::from(ends.0 == ends.1),
+            ends,
+            flag: false,
+            flagged: [false; 2],
+            priority: xorshift(&mut self.seed),
+            l: NONE,
+            r: NONE,
+            p: NONE,
+        };
+
+        match self.free.pop() {
+            Some(x) => {
+                self.nodes[x] = node;
+                x
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn size_of(&self, x: usize) -> usize {
+        if x == NONE {
+            0
+        } else {
+            self.nodes[x].size
+        }
+    }
+
+    fn pull(&mut self, x: usize) {
+        let (l, r) = (self.nodes[x].l, self.nodes[x].r);
+        let kind = if x < self.size { VERTEX } else { EDGE };
+        let node = &self.nodes[x];
+
+        let mut sum = node.val.clone();
+        let mut size = 1;
+        let mut vertices = usize::from(kind == VERTEX);
+        let mut flagged = [false; 2];
+        flagged[kind] = node.flag;
+        if l != NONE {
+            let child = &self.nodes[l];
+            sum = child.sum.clone().op(sum);
+            size += child.size;
+            vertices += child.vertices;
+            flagged = [
+                flagged[0] || child.flagged[0],
+                flagged[1] || child.flagged[1],
+            ];
+        }
+        if r != NONE {
+            let child = &self.nodes[r];
+            sum = sum.op(child.sum.clone());
+            size += child.size;
+            vertices += child.vertices;
+            flagged = [
+                flagged[0] || child.flagged[0],
+                flagged[1] || child.flagged[1],
+            ];
+        }
+
+        let node = &mut self.nodes[x];
+        (node.sum, node.size, node.vertices, node.flagged) = (sum, size, vertices, flagged);
+    }
+
+    // Recomputes the aggregates on the path from x to its root
+    fn pull_up(&mut self, x: usize) {
+        let mut x = x;
+        while x != NONE {
+            self.pull(x);
+            x = self.nodes[x].p;
+        }
+    }
+
+    fn merge_rec(&mut self, a: usize, b: usize) -> usize {
+        if a == NONE {
+            return b;
+        }
+        if b == NONE {
+            return a;
+        }
+
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let r = self.merge_rec(self.nodes[a].r, b);
+            self.nodes[a].r = r;
+            self.nodes[r].p = a;
+            self.pull(a);
+            a
+        } else {
+            let l = self.merge_rec(a, self.nodes[b].l);
+            self.nodes[b].l = l;
+            self.nodes[l].p = b;
+            self.pull(b);
+            b
+        }
+    }
+
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        let root = self.merge_rec(a, b);
+        if root != NONE {
+            self.nodes[root].p = NONE;
+        }
+        root
+    }
+
+    fn split_rec(&mut self, t: usize, k: usize) -> (usize, usize) {
+        if t == NONE {
+            return (NONE, NONE);
+        }
+
+        let l = self.nodes[t].l;
+        let ls = self.size_of(l);
+        if k <= ls {
+            let (a, b) = self.split_rec(l, k);
+            self.nodes[t].l = b;
+            if b != NONE {
+                self.nodes[b].p = t;
+            }
+            self.pull(t);
+            (a, t)
+        } else {
+            let (a, b) = self.split_rec(self.nodes[t].r, k - ls - 1);
+            self.nodes[t].r = a;
+            if a != NONE {
+                self.nodes[a].p = t;
+            }
+            self.pull(t);
+            (t, b)
+        }
+    }
+
+    // Splits the tour rooted at t into its first k nodes and the rest
+    fn split(&mut self, t: usize, k: usize) -> (usize, usize) {
+        let (a, b) = self.split_rec(t, k);
+        for x in [a, b] {
+            if x != NONE {
+                self.nodes[x].p = NONE;
+            }
+        }
+        (a, b)
+    }
+
+    fn root(&self, x: usize) -> usize {
+        let mut x = x;
+        while self.nodes[x].p != NONE {
+            x = self.nodes[x].p;
+        }
+        x
+    }
+
+    // Position of x in its tour
+    fn index(&self, x: usize) -> usize {
+        let mut idx = self.size_of(self.nodes[x].l);
+        let mut x = x;
+        while self.nodes[x].p != NONE {
+            let p = self.nodes[x].p;
+            if self.nodes[p].r == x {
+                idx += self.size_of(self.nodes[p].l) + 1;
+            }
+            x = p;
+        }
+        idx
+    }
+
+    // Rotates the tour containing v so that it starts at v, returning its root
+    fn reroot(&mut self, v: usize) -> usize {
+        let i = self.index(v);
+        let (a, b) = self.split(self.root(v), i);
+        self.merge(b, a)
+    }
+
+    fn set_flag(&mut self, x: usize, flag: bool) {
+        self.nodes[x].flag = flag;
+        self.pull_up(x);
+    }
+
+    // Returns a flagged node of the given kind in the tree containing v
+    fn find_flagged(&self, v: usize, kind: usize) -> Option<usize> {
+        let mut x = self.root(v);
+        if !self.nodes[x].flagged[kind] {
+            return None;
+        }
+
+        loop {
+            let node = &self.nodes[x];
+            if node.l != NONE && self.nodes[node.l].flagged[kind] {
+                x = node.l;
+            } else if node.flag && (x < self.size) == (kind == VERTEX) {
+                return Some(x);
+            } else {
+                x = node.r;
+            }
+        }
+    }
+
+    /// Returns [`true`] if `u` and `v` are in the same tree
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn connected(&self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+
+    /// Adds the edge between `u` and `v`.
+    /// Returns [`false`], and does nothing, if they were already in the same tree.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::euler_tour_tree::EulerTourTree;
+    ///
+    /// let mut ett: EulerTourTree<i64> = EulerTourTree::new(3);
+    /// assert!(ett.link(0, 1));
+    /// assert!(ett.link(1, 2));
+    /// assert!(!ett.link(0, 2));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+
+        let tu = self.reroot(u);
+        let tv = self.reroot(v);
+        let uv = self.new_node((u, v), T::ID);
+        let vu = self.new_node((v, u), T::ID);
+        self.edges.insert((u, v), uv);
+        self.edges.insert((v, u), vu);
+
+        let t = self.merge(tu, uv);
+        let t = self.merge(t, tv);
+        self.merge(t, vu);
+        true
+    }
+
+    /// Removes the edge between `u` and `v`.
+    /// Returns [`false`] if there was no such edge.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::euler_tour_tree::EulerTourTree;
+    ///
+    /// let mut ett: EulerTourTree<i64> = EulerTourTree::new(3);
+    /// ett.link(0, 1);
+    /// ett.link(1, 2);
+    /// assert!(!ett.cut(0, 2));
+    /// assert!(ett.cut(2, 1));
+    /// assert!(!ett.connected(0, 2));
+    /// assert!(ett.connected(0, 1));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        let Some(uv) = self.edges.remove(&(u, v)) else {
+            return false;
+        };
+        let vu = self.edges.remove(&(v, u)).unwrap();
+
+        let (i, j) = (self.index(uv), self.index(vu));
+        let (i, j) = (i.min(j), i.max(j));
+        // The tour is A uv B vu C, where B is the tour of the tree being split off
+        let (rest, c) = self.split(self.root(uv), j + 1);
+        let (rest, _) = self.split(rest, j);
+        let (rest, _) = self.split(rest, i + 1);
+        let (a, _) = self.split(rest, i);
+        self.merge(a, c);
+
+        self.free.extend([uv, vu]);
+        true
+    }
+
+    /// Returns the value of vertex `v`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `v` is not a valid vertex.
+    pub fn get(&self, v: usize) -> T {
+        debug_assert!(v < self.size);
+        self.nodes[v].val.clone()
+    }
+
+    /// Replaces the value of vertex `v` with `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `v` is not a valid vertex.
+    pub fn set(&mut self, v: usize, val: T) {
+        debug_assert!(v < self.size);
+        self.nodes[v].val = val;
+        self.pull_up(v);
+    }
+
+    /// Returns the number of vertices in the tree containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid vertex.
+    pub fn component_size(&self, v: usize) -> usize {
+        self.nodes[self.root(v)].vertices
+    }
+
+    /// Returns the fold of the values in the tree containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid vertex.
+    pub fn component_fold(&self, v: usize) -> T {
+        self.nodes[self.root(v)].sum.clone()
+    }
+
+    /// Returns the fold of the values in the subtree of `v`, when its tree is rooted so that `parent` is the parent of `v`,
+    /// or [`None`] if there is no edge between them
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::euler_tour_tree::EulerTourTree;
+    ///
+    /// let mut ett = EulerTourTree::from(&[1, 10, 100, 1000]);
+    /// ett.link(0, 1);
+    /// ett.link(1, 2);
+    /// ett.link(2, 3);
+    /// assert_eq!(ett.subtree_fold(1, 0), Some(1110));
+    /// assert_eq!(ett.subtree_fold(1, 2), Some(11));
+    /// assert_eq!(ett.subtree_fold(0, 2), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `v` or `parent` is not a valid vertex.
+    pub fn subtree_fold(&mut self, v: usize, parent: usize) -> Option<T> {
+        let (&down, &up) = (self.edges.get(&(parent, v))?, self.edges.get(&(v, parent))?);
+
+        let root = self.reroot(parent);
+        let (i, j) = (self.index(down), self.index(up));
+        let (rest, c) = self.split(root, j);
+        let (a, b) = self.split(rest, i + 1);
+        let sum = self.nodes[b].sum.clone();
+        let t = self.merge(a, b);
+        self.merge(t, c);
+        Some(sum)
+    }
+}
+
+/// Fully dynamic connectivity, through the Holm–de Lichtenberg–Thorup algorithm
+///
+/// Maintains a spanning forest of a graph on vertices $[0, N)$ with one [`EulerTourTree`] per level,
+/// where edges only move up in levels and there are $\mathcal{O}(\log N)$ levels.
+/// Self-loops are ignored and there is at most one edge between two vertices.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::euler_tour_tree::DynamicConnectivity;
+///
+/// let mut graph = DynamicConnectivity::new(4);
+/// graph.insert(0, 1);
+/// graph.insert(1, 2);
+/// graph.insert(2, 0);
+/// assert_eq!(graph.components(), 2);
+///
+/// graph.remove(0, 1);
+/// assert!(graph.connected(0, 1));
+/// graph.remove(1, 2);
+/// assert!(!graph.connected(0, 1));
+/// assert_eq!(graph.components(), 3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DynamicConnectivity {
+    forests: Vec<EulerTourTree<usize>>,
+    // Non-tree edges of each level, as adjacency sets
+    non_tree: Vec<Vec<HashSet<usize>>>,
+    // Level of each edge, and whether it is a tree edge
+    edges: HashMap<(usize, usize), (usize, bool)>,
+    size: usize,
+    components: usize,
+}
+
+impl DynamicConnectivity {
+    /// Builds a graph of `size` vertices and no edges
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::euler_tour_tree::DynamicConnectivity;
+    ///
+    /// let graph = DynamicConnectivity::new(10);
+    /// assert_eq!(graph.components(), 10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        let mut graph = DynamicConnectivity {
+            forests: vec![],
+            non_tree: vec![],
+            edges: HashMap::new(),
+            size,
+            components: size,
+        };
+        graph.add_level();
+        graph
+    }
+
+    /// Returns the number of vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns [`true`] if the graph has no vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the number of connected components
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    fn add_level(&mut self) {
+        self.forests.push(EulerTourTree::new(self.size));
+        self.non_tree.push(vec![HashSet::new(); self.size]);
+    }
+
+    fn add_tree_edge(&mut self, level: usize, u: usize, v: usize) {
+        self.forests[level].link(u, v);
+        let node = self.forests[level].edges[&(u.min(v), u.max(v))];
+        self.forests[level].set_flag(node, true);
+    }
+
+    fn add_non_tree_edge(&mut self, level: usize, u: usize, v: usize) {
+        for (a, b) in [(u, v), (v, u)] {
+            self.non_tree[level][a].insert(b);
+            self.forests[level].set_flag(a, true);
+        }
+    }
+
+    fn remove_non_tree_edge(&mut self, level: usize, u: usize, v: usize) {
+        for (a, b) in [(u, v), (v, u)] {
+            self.non_tree[level][a].remove(&b);
+            if self.non_tree[level][a].is_empty() {
+                self.forests[level].set_flag(a, false);
+            }
+        }
+    }
+
+    /// Adds the edge between `u` and `v`.
+    /// Returns [`false`], and does nothing, if it already exists or is a self-loop.
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn insert(&mut self, u: usize, v: usize) -> bool {
+        let key = (u.min(v), u.max(v));
+        if u == v || self.edges.contains_key(&key) {
+            return false;
+        }
+
+        let tree = !self.forests[0].connected(u, v);
+        if tree {
+            self.add_tree_edge(0, u, v);
+            self.components -= 1;
+        } else {
+            self.add_non_tree_edge(0, u, v);
+        }
+        self.edges.insert(key, (0, tree));
+        true
+    }
+
+    /// Removes the edge between `u` and `v`.
+    /// Returns [`false`] if there was no such edge.
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn remove(&mut self, u: usize, v: usize) -> bool {
+        let key = (u.min(v), u.max(v));
+        let Some((level, tree)) = self.edges.remove(&key) else {
+            return false;
+        };
+
+        if !tree {
+            self.remove_non_tree_edge(level, u, v);
+            return true;
+        }
+
+        for forest in &mut self.forests[..=level] {
+            forest.cut(u, v);
+        }
+        if !(0..=level).rev().any(|i| self.replace(i, u, v)) {
+            self.components += 1;
+        }
+        true
+    }
+
+    // Looks for an edge of the given level reconnecting u and v, after the removal of the tree edge between them
+    fn replace(&mut self, level: usize, u: usize, v: usize) -> bool {
+        let forest = &self.forests[level];
+        let small = if forest.component_size(u) <= forest.component_size(v) {
+            u
+        } else {
+            v
+        };
+
+        // The smaller side has at most half the vertices of the component, so its tree edges can go one level up
+        while let Some(node) = self.forests[level].find_flagged(small, EDGE) {
+            let (a, b) = self.forests[level].nodes[node].ends;
+            self.forests[level].set_flag(node, false);
+            if level + 1 == self.forests.len() {
+                self.add_level();
+            }
+            self.add_tree_edge(level + 1, a, b);
+            self.edges.insert((a.min(b), a.max(b)), (level + 1, true));
+        }
+
+        while let Some(x) = self.forests[level].find_flagged(small, VERTEX) {
+            while let Some(&y) = self.non_tree[level][x].iter().next() {
+                self.remove_non_tree_edge(level, x, y);
+                let key = (x.min(y), x.max(y));
+
+                if self.forests[level].connected(small, y) {
+                    if level + 1 == self.forests.len() {
+                        self.add_level();
+                    }
+                    self.add_non_tree_edge(level + 1, x, y);
+                    self.edges.insert(key, (level + 1, false));
+                } else {
+                    for forest in &mut self.forests[..level] {
+                        forest.link(x, y);
+                    }
+                    self.add_tree_edge(level, x, y);
+                    self.edges.insert(key, (level, true));
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns [`true`] if `u` and `v` are connected
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn connected(&self, u: usize, v: usize) -> bool {
+        self.forests[0].connected(u, v)
+    }
+
+    /// Returns the number of vertices in the component containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid vertex.
+    pub fn component_size(&self, v: usize) -> usize {
+        self.forests[0].component_size(v)
+    }
+}
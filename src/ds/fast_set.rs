@@ -0,0 +1,297 @@
+const BITS: usize = 64;
+
+/// Set of integers in $[0, N)$, stored as a tree of 64-bit words
+///
+/// Each bit of a word of a level tells whether the corresponding word of the level below is non-empty,
+/// so that all operations take $\mathcal{O}(\log_{64} N)$ word operations.
+///
+/// # Example
+///
+/// Assigning each request to the first free slot not before the requested one:
+///
+/// ```
+/// use cp_library::ds::fast_set::FastSet;
+///
+/// let mut free = FastSet::new(5);
+/// for i in 0..5 {
+///     free.insert(i);
+/// }
+///
+/// let mut slots = vec![];
+/// for request in [1, 1, 0, 1, 4] {
+///     let slot = free.next(request);
+///     if let Some(slot) = slot {
+///         free.remove(slot);
+///     }
+///     slots.push(slot);
+/// }
+///
+/// assert_eq!(slots, vec![Some(1), Some(2), Some(0), Some(3), Some(4)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FastSet {
+    levels: Vec<Vec<u64>>,
+    size: usize,
+    len: usize,
+}
+
+impl FastSet {
+    /// Creates an empty set over the universe $[0, N)$
+    ///
+    /// Complexity: $\mathcal{O}(N / 64)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let set = FastSet::new(1_000_000);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        let mut levels = vec![];
+        let mut m = size;
+        loop {
+            let words = m.div_ceil(BITS).max(1);
+            levels.push(vec![0; words]);
+            if words == 1 {
+                break;
+            }
+            m = words;
+        }
+
+        FastSet {
+            levels,
+            size,
+            len: 0,
+        }
+    }
+
+    /// Returns the size $N$ of the universe
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of elements in the set
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns [`true`] if the set is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns [`true`] if `x` is in the set
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let mut set = FastSet::new(100);
+    /// set.insert(42);
+    /// assert!(set.contains(42));
+    /// assert!(!set.contains(43));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `x` is not in the universe.
+    pub fn contains(&self, x: usize) -> bool {
+        debug_assert!(x < self.size);
+        (self.levels[0][x / BITS] >> (x % BITS)) & 1 == 1
+    }
+
+    /// Inserts `x` in the set.
+    /// Returns [`true`] if `x` was not already present.
+    ///
+    /// Complexity: $\mathcal{O}(\log_{64} N)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let mut set = FastSet::new(100);
+    /// assert!(set.insert(42));
+    /// assert!(!set.insert(42));
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `x` is not in the universe.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let mut set = FastSet::new(100);
+    /// set.insert(100);
+    /// ```
+    pub fn insert(&mut self, x: usize) -> bool {
+        if self.contains(x) {
+            return false;
+        }
+
+        let mut x = x;
+        for level in &mut self.levels {
+            level[x / BITS] |= 1 << (x % BITS);
+            x /= BITS;
+        }
+        self.len += 1;
+        true
+    }
+
+    /// Removes `x` from the set.
+    /// Returns [`true`] if `x` was present.
+    ///
+    /// Complexity: $\mathcal{O}(\log_{64} N)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let mut set = FastSet::new(100);
+    /// set.insert(42);
+    /// assert!(set.remove(42));
+    /// assert!(!set.remove(42));
+    /// assert!(set.is_empty());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `x` is not in the universe.
+    pub fn remove(&mut self, x: usize) -> bool {
+        if !self.contains(x) {
+            return false;
+        }
+
+        let mut x = x;
+        for level in &mut self.levels {
+            level[x / BITS] &= !(1 << (x % BITS));
+            if level[x / BITS] != 0 {
+                break;
+            }
+            x /= BITS;
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Returns the smallest element of the set not smaller than `x`, or [`None`] if none exists
+    ///
+    /// Complexity: $\mathcal{O}(\log_{64} N)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let mut set = FastSet::new(10_000);
+    /// set.insert(5);
+    /// set.insert(9_000);
+    /// assert_eq!(set.next(5), Some(5));
+    /// assert_eq!(set.next(6), Some(9_000));
+    /// assert_eq!(set.next(9_001), None);
+    /// assert_eq!(set.next(20_000), None);
+    /// ```
+    pub fn next(&self, x: usize) -> Option<usize> {
+        let mut x = x;
+        for (h, level) in self.levels.iter().enumerate() {
+            let word = *level.get(x / BITS)?;
+            let rest = word.checked_shr((x % BITS) as u32).unwrap_or(0);
+            if rest == 0 {
+                x = x / BITS + 1;
+                continue;
+            }
+
+            x += rest.trailing_zeros() as usize;
+            for below in self.levels[..h].iter().rev() {
+                x = x * BITS + below[x].trailing_zeros() as usize;
+            }
+            return Some(x);
+        }
+        None
+    }
+
+    /// Returns the largest element of the set not larger than `x`, or [`None`] if none exists
+    ///
+    /// Complexity: $\mathcal{O}(\log_{64} N)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let mut set = FastSet::new(10_000);
+    /// set.insert(5);
+    /// set.insert(9_000);
+    /// assert_eq!(set.prev(9_000), Some(9_000));
+    /// assert_eq!(set.prev(8_999), Some(5));
+    /// assert_eq!(set.prev(4), None);
+    /// assert_eq!(set.prev(20_000), Some(9_000));
+    /// ```
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        if self.size == 0 {
+            return None;
+        }
+
+        let mut x = x.min(self.size - 1);
+        for (h, level) in self.levels.iter().enumerate() {
+            let rest = level[x / BITS] << (BITS - 1 - x % BITS);
+            if rest == 0 {
+                if x < BITS {
+                    return None;
+                }
+                x = x / BITS - 1;
+                continue;
+            }
+
+            x -= rest.leading_zeros() as usize;
+            for below in self.levels[..h].iter().rev() {
+                x = x * BITS + (BITS - 1 - below[x].leading_zeros() as usize);
+            }
+            return Some(x);
+        }
+        None
+    }
+
+    /// Returns the smallest element of the set, or [`None`] if the set is empty
+    ///
+    /// Complexity: $\mathcal{O}(\log_{64} N)$
+    pub fn min(&self) -> Option<usize> {
+        self.next(0)
+    }
+
+    /// Returns the largest element of the set, or [`None`] if the set is empty
+    ///
+    /// Complexity: $\mathcal{O}(\log_{64} N)$
+    pub fn max(&self) -> Option<usize> {
+        self.prev(usize::MAX)
+    }
+
+    /// Returns an iterator over the elements of the set, in increasing order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fast_set::FastSet;
+    ///
+    /// let mut set = FastSet::new(1000);
+    /// set.insert(700);
+    /// set.insert(3);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 700]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.min(), |&x| self.next(x + 1))
+    }
+}
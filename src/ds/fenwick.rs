@@ -0,0 +1,898 @@
+use crate::ds::coord_comp::CoordinateCompressor;
+use crate::math::algebra::{Abelian, Monoid};
+
+/// Fenwick tree (binary indexed tree) over an [abelian group](https://en.wikipedia.org/wiki/Abelian_group) `T`
+///
+/// Supports point updates and range sums.
+///
+/// `T` must be [`Clone`] and [`Abelian`]
+#[derive(Clone, Debug)]
+pub struct Fenwick<T> {
+    arr: Vec<T>,
+}
+
+impl<T> Fenwick<T>
+where
+    T: Clone + Abelian,
+{
+    /// Builds a Fenwick tree of given `size`, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick;
+    ///
+    /// let x: Fenwick<i64> = Fenwick::new(10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        Fenwick {
+            arr: vec![T::ID; size],
+        }
+    }
+
+    /// Builds a Fenwick tree from a slice of `T`.
+    ///
+    /// Complexity: $\mathcal{O}(N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick;
+    ///
+    /// let x = Fenwick::from(&[1, 2, 3, 4]);
+    /// ```
+    pub fn from(array: &[T]) -> Self {
+        let mut arr = array.to_vec();
+        for i in 0..arr.len() {
+            let j = i | (i + 1);
+            if j < arr.len() {
+                arr[j] = arr[j].clone().op(arr[i].clone());
+            }
+        }
+        Fenwick { arr }
+    }
+
+    /// Returns the size of the Fenwick tree
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.arr.len()
+    }
+
+    /// Returns [`true`] if the Fenwick tree has size $0$
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.arr.is_empty()
+    }
+
+    /// Adds `val` to the element at `pos`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick;
+    ///
+    /// let mut x = Fenwick::from(&[1, 2, 3, 4]);
+    /// x.add(2, 5);
+    /// assert_eq!(x.query(1, 3), 10);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::Fenwick;
+    ///
+    /// let mut x = Fenwick::from(&[1, 2, 3, 4]);
+    /// x.add(4, 5);
+    /// ```
+    pub fn add(&mut self, pos: usize, val: T) {
+        debug_assert!(pos < self.arr.len());
+
+        let mut pos = pos;
+        while pos < self.arr.len() {
+            self.arr[pos] = self.arr[pos].clone().op(val.clone());
+            pos |= pos + 1;
+        }
+    }
+
+    /// Returns the sum of the range $[0, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick;
+    ///
+    /// let x = Fenwick::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.prefix(3), 6);
+    /// assert_eq!(x.prefix(0), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `r` is greater than the size of the Fenwick tree.
+    pub fn prefix(&self, r: usize) -> T {
+        debug_assert!(r <= self.arr.len());
+
+        let mut ans = T::ID;
+        let mut r = r;
+        while r > 0 {
+            ans = ans.op(self.arr[r - 1].clone());
+            r &= r - 1;
+        }
+        ans
+    }
+
+    /// Returns the sum of the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick;
+    ///
+    /// let x = Fenwick::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.query(1, 3), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range:
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::Fenwick;
+    ///
+    /// let x = Fenwick::from(&[1, 2, 3, 4]);
+    /// let y = x.query(3, 2);
+    /// ```
+    pub fn query(&self, l: usize, r: usize) -> T {
+        debug_assert!(l <= r && r <= self.arr.len());
+        self.prefix(r).op(self.prefix(l).inv())
+    }
+}
+
+/// Two-dimensional Fenwick tree over an [abelian group](https://en.wikipedia.org/wiki/Abelian_group) `T`
+///
+/// Supports point updates and rectangle sums on a dense grid.
+///
+/// `T` must be [`Clone`] and [`Abelian`]
+#[derive(Clone, Debug)]
+pub struct Fenwick2D<T> {
+    arr: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Fenwick2D<T>
+where
+    T: Clone + Abelian,
+{
+    /// Builds a Fenwick tree over a grid with `rows` rows and `cols` columns, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(NM)$ where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick2D;
+    ///
+    /// let x: Fenwick2D<i64> = Fenwick2D::new(3, 4);
+    /// ```
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Fenwick2D {
+            arr: vec![T::ID; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Adds `val` to the element in cell $(x, y)$
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log M)$ group operations where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick2D;
+    ///
+    /// let mut x = Fenwick2D::new(3, 4);
+    /// x.add(1, 2, 5);
+    /// x.add(2, 3, 1);
+    /// assert_eq!(x.query(0, 2, 0, 4), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if $(x, y)$ is not a valid cell.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::Fenwick2D;
+    ///
+    /// let mut x = Fenwick2D::new(3, 4);
+    /// x.add(1, 4, 5);
+    /// ```
+    pub fn add(&mut self, x: usize, y: usize, val: T) {
+        debug_assert!(x < self.rows && y < self.cols);
+
+        let mut i = x;
+        while i < self.rows {
+            let mut j = y;
+            while j < self.cols {
+                let cell = &mut self.arr[i * self.cols + j];
+                *cell = cell.clone().op(val.clone());
+                j |= j + 1;
+            }
+            i |= i + 1;
+        }
+    }
+
+    /// Returns the sum of the rectangle $[0, x) \times [0, y)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log M)$ group operations where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick2D;
+    ///
+    /// let mut x = Fenwick2D::new(3, 4);
+    /// x.add(1, 2, 5);
+    /// assert_eq!(x.prefix(2, 3), 5);
+    /// assert_eq!(x.prefix(2, 2), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if $x$ or $y$ exceed the dimensions of the grid.
+    pub fn prefix(&self, x: usize, y: usize) -> T {
+        debug_assert!(x <= self.rows && y <= self.cols);
+
+        let mut ans = T::ID;
+        let mut i = x;
+        while i > 0 {
+            let mut j = y;
+            while j > 0 {
+                ans = ans.op(self.arr[(i - 1) * self.cols + j - 1].clone());
+                j &= j - 1;
+            }
+            i &= i - 1;
+        }
+        ans
+    }
+
+    /// Returns the sum of the rectangle $[x_l, x_r) \times [y_l, y_r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log M)$ group operations where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::Fenwick2D;
+    ///
+    /// let mut x = Fenwick2D::new(3, 4);
+    /// x.add(0, 0, 1);
+    /// x.add(1, 2, 5);
+    /// x.add(2, 1, 7);
+    /// assert_eq!(x.query(1, 3, 1, 3), 12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if the ranges are not valid.
+    pub fn query(&self, xl: usize, xr: usize, yl: usize, yr: usize) -> T {
+        debug_assert!(xl <= xr && yl <= yr);
+
+        self.prefix(xr, yr)
+            .op(self.prefix(xl, yr).inv())
+            .op(self.prefix(xr, yl).inv())
+            .op(self.prefix(xl, yl))
+    }
+}
+
+/// Two-dimensional Fenwick tree over an [abelian group](https://en.wikipedia.org/wiki/Abelian_group) `T`,
+/// for a set of points known in advance
+///
+/// Coordinates of type `K` are compressed with a [`CoordinateCompressor`],
+/// so that memory is proportional to the number of points times their logarithm.
+///
+/// `K` must be [`Ord`] and [`Clone`], `T` must be [`Clone`] and [`Abelian`]
+#[derive(Clone, Debug)]
+pub struct SparseFenwick2D<K, T> {
+    xs: CoordinateCompressor<K>,
+    ys: Vec<CoordinateCompressor<K>>,
+    trees: Vec<Fenwick<T>>,
+}
+
+impl<K, T> SparseFenwick2D<K, T>
+where
+    K: Ord + Clone,
+    T: Clone + Abelian,
+{
+    /// Builds a Fenwick tree over the given `points`, all holding identity elements
+    ///
+    /// Complexity: $\mathcal{O}(N \log^2 N)$ comparisons where:
+    /// - $N$ is the number of points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::SparseFenwick2D;
+    ///
+    /// let x: SparseFenwick2D<i64, i64> = SparseFenwick2D::new(&[(0, 1_000_000_000), (-5, 3)]);
+    /// ```
+    pub fn new(points: &[(K, K)]) -> Self {
+        let xs = CoordinateCompressor::from_coords(points.iter().map(|p| p.0.clone()).collect());
+
+        let mut ys = vec![vec![]; xs.size()];
+        for (x, y) in points {
+            let mut i = xs.compress(x);
+            while i < xs.size() {
+                ys[i].push(y.clone());
+                i |= i + 1;
+            }
+        }
+
+        let ys: Vec<_> = ys
+            .into_iter()
+            .map(CoordinateCompressor::from_coords)
+            .collect();
+        let trees = ys.iter().map(|y| Fenwick::new(y.size())).collect();
+
+        SparseFenwick2D { xs, ys, trees }
+    }
+
+    /// Adds `val` to the element in point $(x, y)$
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ group operations and comparisons where:
+    /// - $N$ is the number of points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::SparseFenwick2D;
+    ///
+    /// let mut x = SparseFenwick2D::new(&[(0, 1_000_000_000), (-5, 3)]);
+    /// x.add(&0, &1_000_000_000, 4);
+    /// assert_eq!(x.query(&0, &1, &0, &i64::MAX), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if $(x, y)$ is not one of the points.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::SparseFenwick2D;
+    ///
+    /// let mut x = SparseFenwick2D::new(&[(0, 1_000_000_000), (-5, 3)]);
+    /// x.add(&0, &5, 4);
+    /// ```
+    pub fn add(&mut self, x: &K, y: &K, val: T) {
+        let mut i = self.xs.compress(x);
+        while i < self.xs.size() {
+            let j = self.ys[i].compress(y);
+            self.trees[i].add(j, val.clone());
+            i |= i + 1;
+        }
+    }
+
+    /// Returns the sum of the points in $(-\infty, x) \times (-\infty, y)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ group operations and comparisons where:
+    /// - $N$ is the number of points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::SparseFenwick2D;
+    ///
+    /// let mut x = SparseFenwick2D::new(&[(0, 10), (-5, 3), (7, -2)]);
+    /// x.add(&0, &10, 4);
+    /// x.add(&-5, &3, 1);
+    /// x.add(&7, &-2, 2);
+    /// assert_eq!(x.prefix(&1, &11), 5);
+    /// assert_eq!(x.prefix(&8, &4), 3);
+    /// ```
+    pub fn prefix(&self, x: &K, y: &K) -> T {
+        let mut ans = T::ID;
+        let mut i = self.xs.lower_bound(x);
+        while i > 0 {
+            let j = self.ys[i - 1].lower_bound(y);
+            ans = ans.op(self.trees[i - 1].prefix(j));
+            i &= i - 1;
+        }
+        ans
+    }
+
+    /// Returns the sum of the points in $[x_l, x_r) \times [y_l, y_r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ group operations and comparisons where:
+    /// - $N$ is the number of points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::SparseFenwick2D;
+    ///
+    /// let mut x = SparseFenwick2D::new(&[(0, 10), (-5, 3), (7, -2)]);
+    /// x.add(&0, &10, 4);
+    /// x.add(&-5, &3, 1);
+    /// x.add(&7, &-2, 2);
+    /// assert_eq!(x.query(&-5, &7, &0, &20), 5);
+    /// assert_eq!(x.query(&-4, &8, &-2, &10), 2);
+    /// ```
+    pub fn query(&self, xl: &K, xr: &K, yl: &K, yr: &K) -> T {
+        self.prefix(xr, yr)
+            .op(self.prefix(xl, yr).inv())
+            .op(self.prefix(xr, yl).inv())
+            .op(self.prefix(xl, yl))
+    }
+}
+
+// Returns `x` combined with itself `k` times, by repeated doubling
+fn times<T: Clone + Abelian>(x: T, k: usize) -> T {
+    let (mut ans, mut x, mut k) = (T::ID, x, k);
+    while k > 0 {
+        if k & 1 == 1 {
+            ans = ans.op(x.clone());
+        }
+        x = x.clone().op(x);
+        k >>= 1;
+    }
+    ans
+}
+
+/// Fenwick tree over the differences of an array over an [abelian group](https://en.wikipedia.org/wiki/Abelian_group) `T`
+///
+/// Supports range updates and point queries.
+///
+/// `T` must be [`Clone`] and [`Abelian`]
+#[derive(Clone, Debug)]
+pub struct DiffFenwick<T> {
+    diff: Fenwick<T>,
+}
+
+impl<T> DiffFenwick<T>
+where
+    T: Clone + Abelian,
+{
+    /// Builds a Fenwick tree of given `size`, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::DiffFenwick;
+    ///
+    /// let x: DiffFenwick<i64> = DiffFenwick::new(10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        DiffFenwick {
+            diff: Fenwick::new(size),
+        }
+    }
+
+    /// Builds a Fenwick tree from a slice of `T`.
+    ///
+    /// Complexity: $\mathcal{O}(N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::DiffFenwick;
+    ///
+    /// let x = DiffFenwick::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.get(2), 3);
+    /// ```
+    pub fn from(array: &[T]) -> Self {
+        let diff: Vec<_> = (0..array.len())
+            .map(|i| match i {
+                0 => array[0].clone(),
+                _ => array[i].clone().op(array[i - 1].clone().inv()),
+            })
+            .collect();
+        DiffFenwick {
+            diff: Fenwick::from(&diff),
+        }
+    }
+
+    /// Returns the size of the Fenwick tree
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.diff.len()
+    }
+
+    /// Returns [`true`] if the Fenwick tree has size $0$
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.diff.is_empty()
+    }
+
+    /// Adds `val` to all the elements in the range $[l, r)$
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::DiffFenwick;
+    ///
+    /// let mut x = DiffFenwick::from(&[1, 2, 3, 4]);
+    /// x.add(1, 3, 10);
+    /// assert_eq!(x.get(0), 1);
+    /// assert_eq!(x.get(2), 13);
+    /// assert_eq!(x.get(3), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::DiffFenwick;
+    ///
+    /// let mut x = DiffFenwick::from(&[1, 2, 3, 4]);
+    /// x.add(1, 5, 10);
+    /// ```
+    pub fn add(&mut self, l: usize, r: usize, val: T) {
+        debug_assert!(l <= r && r <= self.len());
+        if l == r {
+            return;
+        }
+
+        self.diff.add(l, val.clone());
+        if r < self.len() {
+            self.diff.add(r, val.inv());
+        }
+    }
+
+    /// Returns the element at `pos`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::DiffFenwick;
+    ///
+    /// let x = DiffFenwick::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.get(1), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::DiffFenwick;
+    ///
+    /// let x = DiffFenwick::from(&[1, 2, 3, 4]);
+    /// x.get(4);
+    /// ```
+    pub fn get(&self, pos: usize) -> T {
+        debug_assert!(pos < self.len());
+        self.diff.prefix(pos + 1)
+    }
+}
+
+/// Fenwick tree over an [abelian group](https://en.wikipedia.org/wiki/Abelian_group) `T`
+/// with range updates and range sums
+///
+/// Keeps two Fenwick trees over the differences, so that adding $v$ to $[l, r)$ adds
+/// $v \cdot (p - l)$ to the sum of $[0, p)$ for $l \le p \le r$; multiples are computed by repeated doubling.
+///
+/// `T` must be [`Clone`] and [`Abelian`]
+#[derive(Clone, Debug)]
+pub struct RangeFenwick<T> {
+    diff: Fenwick<T>,
+    // Differences multiplied by their index
+    weighted: Fenwick<T>,
+}
+
+impl<T> RangeFenwick<T>
+where
+    T: Clone + Abelian,
+{
+    /// Builds a Fenwick tree of given `size`, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::RangeFenwick;
+    ///
+    /// let x: RangeFenwick<i64> = RangeFenwick::new(10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        RangeFenwick {
+            diff: Fenwick::new(size),
+            weighted: Fenwick::new(size),
+        }
+    }
+
+    /// Builds a Fenwick tree from a slice of `T`.
+    ///
+    /// Complexity: $\mathcal{O}(N \log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::RangeFenwick;
+    ///
+    /// let x = RangeFenwick::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.query(1, 3), 5);
+    /// ```
+    pub fn from(array: &[T]) -> Self {
+        let diff: Vec<_> = (0..array.len())
+            .map(|i| match i {
+                0 => array[0].clone(),
+                _ => array[i].clone().op(array[i - 1].clone().inv()),
+            })
+            .collect();
+        let weighted: Vec<_> = diff
+            .iter()
+            .enumerate()
+            .map(|(i, d)| times(d.clone(), i))
+            .collect();
+
+        RangeFenwick {
+            diff: Fenwick::from(&diff),
+            weighted: Fenwick::from(&weighted),
+        }
+    }
+
+    /// Returns the size of the Fenwick tree
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.diff.len()
+    }
+
+    /// Returns [`true`] if the Fenwick tree has size $0$
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.diff.is_empty()
+    }
+
+    /// Adds `val` to all the elements in the range $[l, r)$
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::RangeFenwick;
+    ///
+    /// let mut x = RangeFenwick::from(&[1, 2, 3, 4]);
+    /// x.add(1, 3, 10);
+    /// assert_eq!(x.query(0, 4), 30);
+    /// assert_eq!(x.query(2, 4), 17);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::RangeFenwick;
+    ///
+    /// let mut x = RangeFenwick::from(&[1, 2, 3, 4]);
+    /// x.add(3, 2, 10);
+    /// ```
+    pub fn add(&mut self, l: usize, r: usize, val: T) {
+        debug_assert!(l <= r && r <= self.len());
+        if l == r {
+            return;
+        }
+
+        self.diff.add(l, val.clone());
+        self.weighted.add(l, times(val.clone(), l));
+        if r < self.len() {
+            self.diff.add(r, val.clone().inv());
+            self.weighted.add(r, times(val, r).inv());
+        }
+    }
+
+    /// Returns the sum of the range $[0, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `r` is greater than the size of the Fenwick tree.
+    pub fn prefix(&self, r: usize) -> T {
+        debug_assert!(r <= self.len());
+        times(self.diff.prefix(r), r).op(self.weighted.prefix(r).inv())
+    }
+
+    /// Returns the sum of the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn query(&self, l: usize, r: usize) -> T {
+        debug_assert!(l <= r && r <= self.len());
+        self.prefix(r).op(self.prefix(l).inv())
+    }
+}
+
+/// Fenwick tree over a commutative [monoid](https://en.wikipedia.org/wiki/Monoid) `T`, where elements can only be combined with new values
+///
+/// Supports updates of the form $a_i \gets a_i \cdot v$ and prefix folds, without needing inverses.
+/// The typical use is a prefix maximum where values only increase, e.g. for longest increasing subsequences.
+///
+/// `T` must be [`Clone`] and [`Monoid`], and should be commutative
+///
+/// # Example
+///
+/// Longest strictly increasing subsequence:
+///
+/// ```
+/// use cp_library::ds::fenwick::MonotoneFenwick;
+/// use cp_library::math::algebra::{Magma, Monoid, Semigroup};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Max(usize);
+///
+/// impl Magma for Max {
+///     fn op(self, other: Self) -> Self {
+///         Max(self.0.max(other.0))
+///     }
+/// }
+///
+/// impl Semigroup for Max {}
+///
+/// impl Monoid for Max {
+///     const ID: Self = Max(0);
+/// }
+///
+/// let arr = [3, 1, 4, 1, 5, 9, 2, 6];
+/// let mut best = MonotoneFenwick::new(10);
+/// for &x in &arr {
+///     let Max(len) = best.prefix(x);
+///     best.update(x, Max(len + 1));
+/// }
+/// assert_eq!(best.prefix(10), Max(4));
+/// ```
+#[derive(Clone, Debug)]
+pub struct MonotoneFenwick<T> {
+    arr: Vec<T>,
+}
+
+impl<T> MonotoneFenwick<T>
+where
+    T: Clone + Monoid,
+{
+    /// Builds a Fenwick tree of given `size`, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::MonotoneFenwick;
+    ///
+    /// let x: MonotoneFenwick<Option<i64>> = MonotoneFenwick::new(10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        MonotoneFenwick {
+            arr: vec![T::ID; size],
+        }
+    }
+
+    /// Builds a Fenwick tree from a slice of `T`.
+    ///
+    /// Complexity: $\mathcal{O}(N)$ monoid operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::fenwick::MonotoneFenwick;
+    ///
+    /// let x = MonotoneFenwick::from(&[Some(1), Some(2), Some(3)]);
+    /// assert_eq!(x.prefix(2), Some(3));
+    /// ```
+    pub fn from(array: &[T]) -> Self {
+        let mut arr = array.to_vec();
+        for i in 0..arr.len() {
+            let j = i | (i + 1);
+            if j < arr.len() {
+                arr[j] = arr[j].clone().op(arr[i].clone());
+            }
+        }
+        MonotoneFenwick { arr }
+    }
+
+    /// Returns the size of the Fenwick tree
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.arr.len()
+    }
+
+    /// Returns [`true`] if the Fenwick tree has size $0$
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.arr.is_empty()
+    }
+
+    /// Combines the element at `pos` with `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::fenwick::MonotoneFenwick;
+    ///
+    /// let mut x = MonotoneFenwick::from(&[Some(1), Some(2)]);
+    /// x.update(2, Some(5));
+    /// ```
+    pub fn update(&mut self, pos: usize, val: T) {
+        debug_assert!(pos < self.arr.len());
+
+        let mut pos = pos;
+        while pos < self.arr.len() {
+            self.arr[pos] = self.arr[pos].clone().op(val.clone());
+            pos |= pos + 1;
+        }
+    }
+
+    /// Returns the fold of the range $[0, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations where:
+    /// - $N$ is the size of the Fenwick tree.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `r` is greater than the size of the Fenwick tree.
+    pub fn prefix(&self, r: usize) -> T {
+        debug_assert!(r <= self.arr.len());
+
+        let mut ans = T::ID;
+        let mut r = r;
+        while r > 0 {
+            ans = ans.op(self.arr[r - 1].clone());
+            r &= r - 1;
+        }
+        ans
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+use std::ops::{Add, Sub};
+
+/// Set of disjoint half-open intervals $[l, r)$ over a totally ordered `T`
+///
+/// Intervals that overlap or touch are merged on insertion, and removing an interval
+/// splits the ones partially covered by it.
+///
+/// `T` must be [`Copy`], [`Ord`], support addition and subtraction, and have zero as [`Default`] value.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::interval_set::IntervalSet;
+///
+/// let mut set = IntervalSet::new();
+/// set.insert(1, 4);
+/// set.insert(6, 8);
+/// set.insert(4, 5);
+/// set.remove(2, 3);
+///
+/// assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 2), (3, 5), (6, 8)]);
+/// assert_eq!(set.total_length(), 5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct IntervalSet<T> {
+    intervals: BTreeMap<T, T>,
+    length: T,
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Default,
+{
+    /// Creates an empty set
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::IntervalSet;
+    ///
+    /// let set: IntervalSet<i64> = IntervalSet::new();
+    /// ```
+    pub fn new() -> Self {
+        IntervalSet {
+            intervals: BTreeMap::new(),
+            length: T::default(),
+        }
+    }
+
+    /// Returns the number of intervals in the set
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// Returns [`true`] if the set contains no intervals
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns the total length of the intervals in the set
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn total_length(&self) -> T {
+        self.length
+    }
+
+    fn add(&mut self, l: T, r: T) {
+        self.intervals.insert(l, r);
+        self.length = self.length + (r - l);
+    }
+
+    fn erase(&mut self, l: T) -> T {
+        let r = self.intervals.remove(&l).unwrap();
+        self.length = self.length - (r - l);
+        r
+    }
+
+    /// Inserts the interval $[l, r)$, merging it with the intervals it overlaps or touches
+    ///
+    /// Complexity: amortized $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of intervals in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(1, 3);
+    /// set.insert(5, 7);
+    /// set.insert(3, 5);
+    /// assert_eq!(set.len(), 1);
+    /// assert_eq!(set.find(4), Some((1, 7)));
+    /// ```
+    pub fn insert(&mut self, l: T, r: T) {
+        if l >= r {
+            return;
+        }
+
+        let (mut l, mut r) = (l, r);
+        if let Some((&pl, &pr)) = self.intervals.range(..l).next_back() {
+            if pr >= l {
+                l = pl;
+                r = r.max(self.erase(pl));
+            }
+        }
+        while let Some((&nl, _)) = self.intervals.range(l..=r).next() {
+            r = r.max(self.erase(nl));
+        }
+
+        self.add(l, r);
+    }
+
+    /// Removes the interval $[l, r)$ from the set, splitting the intervals it partially covers
+    ///
+    /// Complexity: amortized $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of intervals in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(1, 10);
+    /// set.remove(3, 5);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 3), (5, 10)]);
+    /// ```
+    pub fn remove(&mut self, l: T, r: T) {
+        if l >= r {
+            return;
+        }
+
+        if let Some((&pl, &pr)) = self.intervals.range(..l).next_back() {
+            if pr > l {
+                self.erase(pl);
+                self.add(pl, l);
+                if pr > r {
+                    self.add(r, pr);
+                }
+            }
+        }
+        while let Some((&nl, _)) = self.intervals.range(l..r).next() {
+            let nr = self.erase(nl);
+            if nr > r {
+                self.add(r, nr);
+            }
+        }
+    }
+
+    /// Returns the interval containing `x`, or [`None`] if `x` is not covered
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of intervals in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(1, 3);
+    /// assert_eq!(set.find(1), Some((1, 3)));
+    /// assert_eq!(set.find(3), None);
+    /// ```
+    pub fn find(&self, x: T) -> Option<(T, T)> {
+        self.intervals
+            .range(..=x)
+            .next_back()
+            .filter(|(_, &r)| x < r)
+            .map(|(&l, &r)| (l, r))
+    }
+
+    /// Returns [`true`] if `x` is covered by an interval of the set
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of intervals in the set.
+    pub fn covers(&self, x: T) -> bool {
+        self.find(x).is_some()
+    }
+
+    /// Returns an iterator over the intervals of the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.iter().map(|(&l, &r)| (l, r))
+    }
+}
+
+impl<T> Default for IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Map from a range $[lo, hi)$ of keys `K` to values `V`, stored as maximal pieces of
+/// keys assigned together (also known as Chtholly tree or ODT)
+///
+/// Assigning a value to a range replaces all the pieces inside it, so that the number of pieces
+/// stays small when assignments are frequent; this gives amortized bounds on random data.
+///
+/// `K` must be [`Copy`] and [`Ord`], `V` must be [`Clone`]
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::interval_set::RangeMap;
+///
+/// let mut map = RangeMap::new(0, 10, 0);
+/// map.assign(2, 6, 1);
+/// map.update(4, 8, |v| *v += 10);
+///
+/// let pieces: Vec<_> = map.pieces(0, 10).map(|(l, r, &v)| (l, r, v)).collect();
+/// assert_eq!(pieces, vec![(0, 2, 0), (2, 4, 1), (4, 6, 11), (6, 8, 10), (8, 10, 0)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RangeMap<K, V> {
+    pieces: BTreeMap<K, V>,
+    hi: K,
+}
+
+impl<K, V> RangeMap<K, V>
+where
+    K: Copy + Ord,
+    V: Clone,
+{
+    /// Creates a map over the keys in $[lo, hi)$, all assigned to `val`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if the range is empty.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::interval_set::RangeMap;
+    ///
+    /// let map = RangeMap::new(3, 3, 0);
+    /// ```
+    pub fn new(lo: K, hi: K, val: V) -> Self {
+        debug_assert!(lo < hi);
+
+        RangeMap {
+            pieces: BTreeMap::from([(lo, val)]),
+            hi,
+        }
+    }
+
+    /// Returns the number of pieces
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Returns [`true`] if the map has no pieces, which never happens
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    fn check(&self, x: K) -> bool {
+        self.pieces.first_key_value().unwrap().0 <= &x && x <= self.hi
+    }
+
+    // Ensures that a piece starts at `x`
+    fn split(&mut self, x: K) {
+        if x == self.hi {
+            return;
+        }
+        let (_, val) = self.pieces.range(..=x).next_back().unwrap();
+        let val = val.clone();
+        self.pieces.entry(x).or_insert(val);
+    }
+
+    /// Returns the value assigned to `x`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::RangeMap;
+    ///
+    /// let mut map = RangeMap::new(0, 10, 'a');
+    /// map.assign(3, 5, 'b');
+    /// assert_eq!(map.get(4), &'b');
+    /// assert_eq!(map.get(5), &'a');
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `x` is out of range.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::interval_set::RangeMap;
+    ///
+    /// let map = RangeMap::new(0, 10, 'a');
+    /// map.get(10);
+    /// ```
+    pub fn get(&self, x: K) -> &V {
+        debug_assert!(self.check(x) && x < self.hi);
+        self.pieces.range(..=x).next_back().unwrap().1
+    }
+
+    /// Assigns `val` to all the keys in $[l, r)$, merging them into a single piece
+    ///
+    /// Complexity: amortized $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::RangeMap;
+    ///
+    /// let mut map = RangeMap::new(0, 10, 0);
+    /// map.assign(1, 3, 1);
+    /// map.assign(5, 7, 2);
+    /// map.assign(2, 6, 3);
+    /// assert_eq!(map.len(), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if $[l, r)$ is not a valid range.
+    pub fn assign(&mut self, l: K, r: K, val: V) {
+        debug_assert!(l <= r && self.check(l) && self.check(r));
+        if l == r {
+            return;
+        }
+
+        self.split(l);
+        self.split(r);
+        let inner: Vec<K> = self.pieces.range(l..r).map(|(&k, _)| k).collect();
+        for k in inner {
+            self.pieces.remove(&k);
+        }
+        self.pieces.insert(l, val);
+    }
+
+    /// Applies `f` to the value of every piece in $[l, r)$, splitting the pieces crossing its bounds
+    ///
+    /// Complexity: $\mathcal{O}(\log N + P)$ where:
+    /// - $N$ is the number of pieces;
+    /// - $P$ is the number of pieces in the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::RangeMap;
+    ///
+    /// let mut map = RangeMap::new(0, 10, 1);
+    /// map.update(2, 4, |v| *v *= 5);
+    /// assert_eq!(map.get(1), &1);
+    /// assert_eq!(map.get(3), &5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if $[l, r)$ is not a valid range.
+    pub fn update<F>(&mut self, l: K, r: K, mut f: F)
+    where
+        F: FnMut(&mut V),
+    {
+        debug_assert!(l <= r && self.check(l) && self.check(r));
+        if l == r {
+            return;
+        }
+
+        self.split(l);
+        self.split(r);
+        for (_, val) in self.pieces.range_mut(l..r) {
+            f(val);
+        }
+    }
+
+    /// Returns an iterator over the pieces intersecting $[l, r)$, clipped to it,
+    /// as triples $(l_i, r_i, v_i)$ in increasing order
+    ///
+    /// Complexity: $\mathcal{O}(\log N + P)$ where:
+    /// - $N$ is the number of pieces;
+    /// - $P$ is the number of pieces in the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::interval_set::RangeMap;
+    ///
+    /// let mut map = RangeMap::new(0, 10, 0);
+    /// map.assign(2, 6, 1);
+    /// let sum: i32 = map.pieces(1, 4).map(|(l, r, v)| (r - l) * v).sum();
+    /// assert_eq!(sum, 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if $[l, r)$ is not a valid range.
+    pub fn pieces(&self, l: K, r: K) -> impl Iterator<Item = (K, K, &V)> + '_ {
+        debug_assert!(l <= r && self.check(l) && self.check(r));
+
+        let start = *self.pieces.range(..=l).next_back().unwrap().0;
+        let mut iter = self.pieces.range(start..r).peekable();
+        let hi = self.hi;
+
+        std::iter::from_fn(move || {
+            let (&k, val) = iter.next()?;
+            let end = iter.peek().map_or(hi, |(&k, _)| k).min(r);
+            (k.max(l) < end).then_some((k.max(l), end, val))
+        })
+    }
+}
@@ -0,0 +1,973 @@
+use crate::ds::coord_comp::CoordinateCompressor;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ops::{Add, Mul, Neg, Sub};
+
+const NONE: usize = usize::MAX;
+
+/// Signed integer types usable as coordinates and coefficients of [`Line`]s
+///
+/// All the comparisons are performed exactly, without floating point intersections:
+/// it is up to the user to pick a type wide enough for the values of the lines
+/// (e.g. [`i128`] when slopes and coordinates are up to $10^{18}$).
+///
+/// This trait is implemented for [`i32`], [`i64`], [`i128`] and [`isize`].
+pub trait LineInt:
+    Copy + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    /// The smallest representable value
+    const MIN: Self;
+    /// The largest representable value
+    const MAX: Self;
+    /// The value $1$
+    const ONE: Self;
+
+    /// Returns $\lfloor self / other \rfloor$
+    fn div_floor(self, other: Self) -> Self;
+}
+
+macro_rules! impl_line_int {
+    ($type:ty) => {
+        impl LineInt for $type {
+            const MIN: Self = <$type>::MIN;
+            const MAX: Self = <$type>::MAX;
+            const ONE: Self = 1;
+
+            fn div_floor(self, other: Self) -> Self {
+                let q = self / other;
+                if self % other != 0 && (self < 0) != (other < 0) {
+                    q - 1
+                } else {
+                    q
+                }
+            }
+        }
+    };
+}
+
+impl_line_int!(i32);
+impl_line_int!(i64);
+impl_line_int!(i128);
+impl_line_int!(isize);
+
+/// The line $y = a x + b$
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Line<T> {
+    /// The slope of the line
+    pub a: T,
+    /// The intercept of the line
+    pub b: T,
+}
+
+impl<T: LineInt> Line<T> {
+    /// Creates the line $y = a x + b$
+    pub fn new(a: T, b: T) -> Self {
+        Line { a, b }
+    }
+
+    /// Returns the value of the line at `x`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::Line;
+    ///
+    /// assert_eq!(Line::new(3, -2).eval(4), 10);
+    /// ```
+    pub fn eval(&self, x: T) -> T {
+        self.a * x + self.b
+    }
+
+    fn neg(self) -> Self {
+        Line::new(-self.a, -self.b)
+    }
+
+    // Orients `self` so that the containers can always look for the maximum
+    fn orient(self, min: bool) -> Self {
+        if min {
+            self.neg()
+        } else {
+            self
+        }
+    }
+}
+
+fn orient<T: LineInt>(val: T, min: bool) -> T {
+    if min {
+        -val
+    } else {
+        val
+    }
+}
+
+/// Li Chao tree over a set of integer coordinates known in advance
+///
+/// Stores a set of lines (or segments) and returns the maximum (or minimum) value
+/// they attain at one of the coordinates.
+///
+/// `T` must be [`LineInt`]
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::li_chao::LiChaoTree;
+///
+/// // dp[i] = min_{j < i} dp[j] + (x[i] - x[j])^2
+/// let x = [0, 1, 3, 6, 7];
+/// let mut tree = LiChaoTree::new_min(x.to_vec());
+/// let mut dp = vec![0; x.len()];
+///
+/// tree.add_line(-2 * x[0], x[0] * x[0]);
+/// for i in 1..x.len() {
+///     dp[i] = tree.query(x[i]).unwrap() + x[i] * x[i];
+///     tree.add_line(-2 * x[i], dp[i] + x[i] * x[i]);
+/// }
+///
+/// assert_eq!(dp, vec![0, 1, 5, 14, 15]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LiChaoTree<T> {
+    xs: CoordinateCompressor<T>,
+    lines: Vec<Option<Line<T>>>,
+    size: usize,
+    min: bool,
+}
+
+impl<T: LineInt> LiChaoTree<T> {
+    fn with_orientation(xs: Vec<T>, min: bool) -> Self {
+        let xs = CoordinateCompressor::from_coords(xs);
+        let size = xs.size().next_power_of_two();
+
+        LiChaoTree {
+            xs,
+            lines: vec![None; 2 * size],
+            size,
+            min,
+        }
+    }
+
+    /// Builds an empty Li Chao tree answering maximum queries on the coordinates `xs`
+    ///
+    /// Complexity: $\mathcal{O}(N \log N)$ where:
+    /// - $N$ is the number of coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::LiChaoTree;
+    ///
+    /// let tree: LiChaoTree<i64> = LiChaoTree::new_max(vec![-5, 0, 1_000_000_000]);
+    /// ```
+    pub fn new_max(xs: Vec<T>) -> Self {
+        Self::with_orientation(xs, false)
+    }
+
+    /// Builds an empty Li Chao tree answering minimum queries on the coordinates `xs`
+    ///
+    /// Complexity: $\mathcal{O}(N \log N)$ where:
+    /// - $N$ is the number of coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::LiChaoTree;
+    ///
+    /// let tree: LiChaoTree<i64> = LiChaoTree::new_min(vec![-5, 0, 1_000_000_000]);
+    /// ```
+    pub fn new_min(xs: Vec<T>) -> Self {
+        Self::with_orientation(xs, true)
+    }
+
+    fn x(&self, idx: usize) -> T {
+        self.xs.decompress(idx.min(self.xs.size() - 1))
+    }
+
+    // Inserts `line` in the subtree of `node`, which covers the indices [nl, nr)
+    fn insert(&mut self, node: usize, nl: usize, nr: usize, line: Line<T>) {
+        let (mut node, mut nl, mut nr, mut line) = (node, nl, nr, line);
+
+        while nl < self.xs.size() {
+            let Some(mut keep) = self.lines[node] else {
+                self.lines[node] = Some(line);
+                return;
+            };
+
+            if nr - nl == 1 {
+                let x = self.x(nl);
+                if line.eval(x) > keep.eval(x) {
+                    self.lines[node] = Some(line);
+                }
+                return;
+            }
+
+            let mid = (nl + nr) / 2;
+            let xm = self.x(mid - 1);
+            if line.eval(xm) > keep.eval(xm) {
+                (keep, line) = (line, keep);
+            }
+            self.lines[node] = Some(keep);
+
+            let (xl, xr) = (self.x(nl), self.x(nr - 1));
+            if line.eval(xl) > keep.eval(xl) {
+                (node, nr) = (2 * node, mid);
+            } else if line.eval(xr) > keep.eval(xr) {
+                (node, nl) = (2 * node + 1, mid);
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Inserts the line $y = a x + b$
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::LiChaoTree;
+    ///
+    /// let mut tree = LiChaoTree::new_max(vec![-2, 0, 3]);
+    /// tree.add_line(1, 0);
+    /// tree.add_line(-1, 1);
+    /// assert_eq!(tree.query(-2), Some(3));
+    /// assert_eq!(tree.query(0), Some(1));
+    /// assert_eq!(tree.query(3), Some(3));
+    /// ```
+    pub fn add_line(&mut self, a: T, b: T) {
+        let line = Line::new(a, b).orient(self.min);
+        self.insert(1, 0, self.size, line);
+    }
+
+    /// Inserts the segment $y = a x + b$ for $x \in [l, r)$
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ where:
+    /// - $N$ is the number of coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::LiChaoTree;
+    ///
+    /// let mut tree = LiChaoTree::new_min(vec![0, 1, 2, 3, 4]);
+    /// tree.add_segment(1, 3, 0, 0);
+    /// tree.add_segment(0, 4, 1, 2);
+    /// assert_eq!(tree.query(0), Some(2));
+    /// assert_eq!(tree.query(2), Some(0));
+    /// assert_eq!(tree.query(3), Some(5));
+    /// assert_eq!(tree.query(4), None);
+    /// ```
+    pub fn add_segment(&mut self, l: T, r: T, a: T, b: T) {
+        let line = Line::new(a, b).orient(self.min);
+        let (l, r) = (self.xs.lower_bound(&l), self.xs.lower_bound(&r));
+
+        let (mut l, mut r) = (l + self.size, r + self.size);
+        while l < r {
+            if (l & 1) == 1 {
+                let (nl, nr) = self.range(l);
+                self.insert(l, nl, nr, line);
+                l += 1;
+            }
+            if (r & 1) == 1 {
+                r -= 1;
+                let (nl, nr) = self.range(r);
+                self.insert(r, nl, nr, line);
+            }
+
+            (l, r) = (l >> 1, r >> 1);
+        }
+    }
+
+    // Returns the range of indices covered by `node`
+    fn range(&self, node: usize) -> (usize, usize) {
+        let height = self.size.ilog2() - node.ilog2();
+        let nl = (node << height) - self.size;
+        (nl, nl + (1 << height))
+    }
+
+    /// Returns the maximum (or minimum) value at `x` among the inserted lines and segments,
+    /// or [`None`] if no one of them contains `x`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::LiChaoTree;
+    ///
+    /// let mut tree = LiChaoTree::new_max(vec![-2, 0, 3]);
+    /// assert_eq!(tree.query(0), None);
+    /// tree.add_line(2, 1);
+    /// assert_eq!(tree.query(3), Some(7));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `x` is not one of the coordinates.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::li_chao::LiChaoTree;
+    ///
+    /// let mut tree = LiChaoTree::new_max(vec![-2, 0, 3]);
+    /// tree.add_line(2, 1);
+    /// tree.query(1);
+    /// ```
+    pub fn query(&self, x: T) -> Option<T> {
+        let mut node = self.xs.compress(&x) + self.size;
+        let mut ans = None;
+        while node > 0 {
+            if let Some(line) = self.lines[node] {
+                let val = line.eval(x);
+                ans = Some(ans.map_or(val, |ans: T| ans.max(val)));
+            }
+            node >>= 1;
+        }
+        ans.map(|ans| orient(ans, self.min))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    line: Option<Line<T>>,
+    l: usize,
+    r: usize,
+}
+
+/// Li Chao tree over a range of integer coordinates, allocating nodes only when they are needed
+///
+/// Stores a set of lines (or segments) and returns the maximum (or minimum) value
+/// they attain at a coordinate. Nodes are stored in a single arena.
+///
+/// `T` must be [`LineInt`]
+#[derive(Clone, Debug)]
+pub struct DynamicLiChaoTree<T> {
+    nodes: Vec<Node<T>>,
+    lo: T,
+    hi: T,
+    min: bool,
+}
+
+impl<T: LineInt> DynamicLiChaoTree<T> {
+    fn with_orientation(lo: T, hi: T, min: bool) -> Self {
+        debug_assert!(lo < hi);
+
+        DynamicLiChaoTree {
+            nodes: vec![Node {
+                line: None,
+                l: NONE,
+                r: NONE,
+            }],
+            lo,
+            hi,
+            min,
+        }
+    }
+
+    /// Builds an empty Li Chao tree answering maximum queries on the range $[lo, hi)$
+    ///
+    /// $hi - lo$ must be representable in `T`.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::DynamicLiChaoTree;
+    ///
+    /// let tree: DynamicLiChaoTree<i64> = DynamicLiChaoTree::new_max(-1_000_000_000, 1_000_000_000);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if the range is empty.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::li_chao::DynamicLiChaoTree;
+    ///
+    /// let tree: DynamicLiChaoTree<i64> = DynamicLiChaoTree::new_max(3, 3);
+    /// ```
+    pub fn new_max(lo: T, hi: T) -> Self {
+        Self::with_orientation(lo, hi, false)
+    }
+
+    /// Builds an empty Li Chao tree answering minimum queries on the range $[lo, hi)$
+    ///
+    /// $hi - lo$ must be representable in `T`.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::DynamicLiChaoTree;
+    ///
+    /// let tree: DynamicLiChaoTree<i64> = DynamicLiChaoTree::new_min(-1_000_000_000, 1_000_000_000);
+    /// ```
+    pub fn new_min(lo: T, hi: T) -> Self {
+        Self::with_orientation(lo, hi, true)
+    }
+
+    /// Returns the number of allocated nodes
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn child(&mut self, node: usize, right: bool) -> usize {
+        let old = if right {
+            self.nodes[node].r
+        } else {
+            self.nodes[node].l
+        };
+        if old != NONE {
+            return old;
+        }
+
+        self.nodes.push(Node {
+            line: None,
+            l: NONE,
+            r: NONE,
+        });
+        let new = self.nodes.len() - 1;
+        if right {
+            self.nodes[node].r = new;
+        } else {
+            self.nodes[node].l = new;
+        }
+        new
+    }
+
+    fn mid(nl: T, nr: T) -> T {
+        nl + (nr - nl).div_floor(T::ONE + T::ONE)
+    }
+
+    // Inserts `line` in the subtree of `node`, which covers the range [nl, nr)
+    fn insert(&mut self, node: usize, nl: T, nr: T, line: Line<T>) {
+        let (mut node, mut nl, mut nr, mut line) = (node, nl, nr, line);
+
+        loop {
+            let Some(mut keep) = self.nodes[node].line else {
+                self.nodes[node].line = Some(line);
+                return;
+            };
+
+            if nr - nl == T::ONE {
+                if line.eval(nl) > keep.eval(nl) {
+                    self.nodes[node].line = Some(line);
+                }
+                return;
+            }
+
+            let mid = Self::mid(nl, nr);
+            let xm = mid - T::ONE;
+            if line.eval(xm) > keep.eval(xm) {
+                (keep, line) = (line, keep);
+            }
+            self.nodes[node].line = Some(keep);
+
+            let xr = nr - T::ONE;
+            if line.eval(nl) > keep.eval(nl) {
+                (node, nr) = (self.child(node, false), mid);
+            } else if line.eval(xr) > keep.eval(xr) {
+                (node, nl) = (self.child(node, true), mid);
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Inserts the line $y = a x + b$
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the length of the range of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::DynamicLiChaoTree;
+    ///
+    /// let mut tree = DynamicLiChaoTree::new_max(-1_000_000_000, 1_000_000_000);
+    /// tree.add_line(1, 0);
+    /// tree.add_line(-1, 1);
+    /// assert_eq!(tree.query(-2), Some(3));
+    /// assert_eq!(tree.query(0), Some(1));
+    /// assert_eq!(tree.query(999_999_999), Some(999_999_999));
+    /// ```
+    pub fn add_line(&mut self, a: T, b: T) {
+        let line = Line::new(a, b).orient(self.min);
+        self.insert(0, self.lo, self.hi, line);
+    }
+
+    /// Inserts the segment $y = a x + b$ for $x \in [l, r)$
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ where:
+    /// - $N$ is the length of the range of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::DynamicLiChaoTree;
+    ///
+    /// let mut tree = DynamicLiChaoTree::new_min(-1_000_000_000, 1_000_000_000);
+    /// tree.add_segment(1, 3, 0, 0);
+    /// tree.add_segment(-5, 5, 1, 2);
+    /// assert_eq!(tree.query(0), Some(2));
+    /// assert_eq!(tree.query(2), Some(0));
+    /// assert_eq!(tree.query(3), Some(5));
+    /// assert_eq!(tree.query(5), None);
+    /// ```
+    pub fn add_segment(&mut self, l: T, r: T, a: T, b: T) {
+        let line = Line::new(a, b).orient(self.min);
+        let (l, r) = (l.max(self.lo), r.min(self.hi));
+        if l < r {
+            self.add_segment_rec(0, self.lo, self.hi, l, r, line);
+        }
+    }
+
+    fn add_segment_rec(&mut self, node: usize, nl: T, nr: T, l: T, r: T, line: Line<T>) {
+        if l <= nl && nr <= r {
+            self.insert(node, nl, nr, line);
+            return;
+        }
+
+        let mid = Self::mid(nl, nr);
+        if l < mid {
+            let child = self.child(node, false);
+            self.add_segment_rec(child, nl, mid, l, r, line);
+        }
+        if mid < r {
+            let child = self.child(node, true);
+            self.add_segment_rec(child, mid, nr, l, r, line);
+        }
+    }
+
+    /// Returns the maximum (or minimum) value at `x` among the inserted lines and segments,
+    /// or [`None`] if no one of them contains `x`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the length of the range of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::DynamicLiChaoTree;
+    ///
+    /// let mut tree = DynamicLiChaoTree::<i64>::new_max(0, 1 << 60);
+    /// assert_eq!(tree.query(0), None);
+    /// tree.add_line(2, 1);
+    /// assert_eq!(tree.query(1 << 59), Some((1 << 60) + 1));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `x` is not in the range of the tree.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::li_chao::DynamicLiChaoTree;
+    ///
+    /// let mut tree = DynamicLiChaoTree::new_max(-5, 5);
+    /// tree.query(5);
+    /// ```
+    pub fn query(&self, x: T) -> Option<T> {
+        debug_assert!(self.lo <= x && x < self.hi);
+
+        let (mut node, mut nl, mut nr) = (0, self.lo, self.hi);
+        let mut ans = None;
+        while node != NONE {
+            if let Some(line) = self.nodes[node].line {
+                let val = line.eval(x);
+                ans = Some(ans.map_or(val, |ans: T| ans.max(val)));
+            }
+
+            let mid = Self::mid(nl, nr);
+            if x < mid {
+                (node, nr) = (self.nodes[node].l, mid);
+            } else {
+                (node, nl) = (self.nodes[node].r, mid);
+            }
+        }
+        ans.map(|ans| orient(ans, self.min))
+    }
+}
+
+// Returns the last integer x at which `l` is not below `r`, given that `l.a < r.a`
+fn intersect<T: LineInt>(l: &Line<T>, r: &Line<T>) -> T {
+    (l.b - r.b).div_floor(r.a - l.a)
+}
+
+/// Convex hull trick for lines inserted in order of slope
+///
+/// Lines can be inserted only if their slope is not smaller, or not larger, than all the slopes
+/// already inserted; the structure then returns the maximum (or minimum) value attained by the lines at a point.
+///
+/// `T` must be [`LineInt`]
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::li_chao::MonotoneCht;
+///
+/// // dp[i] = min_{j < i} dp[j] + (x[i] - x[j])^2, with increasing x
+/// let x = [0, 1, 3, 6, 7];
+/// let mut cht = MonotoneCht::new_min();
+/// let mut dp = vec![0; x.len()];
+///
+/// cht.add_line(-2 * x[0], x[0] * x[0]);
+/// for i in 1..x.len() {
+///     dp[i] = cht.query_monotone(x[i]).unwrap() + x[i] * x[i];
+///     cht.add_line(-2 * x[i], dp[i] + x[i] * x[i]);
+/// }
+///
+/// assert_eq!(dp, vec![0, 1, 5, 14, 15]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MonotoneCht<T> {
+    // Oriented lines of the upper hull, by increasing slope
+    hull: VecDeque<Line<T>>,
+    min: bool,
+}
+
+impl<T: LineInt> MonotoneCht<T> {
+    /// Creates an empty container answering maximum queries
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn new_max() -> Self {
+        MonotoneCht {
+            hull: VecDeque::new(),
+            min: false,
+        }
+    }
+
+    /// Creates an empty container answering minimum queries
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn new_min() -> Self {
+        MonotoneCht {
+            hull: VecDeque::new(),
+            min: true,
+        }
+    }
+
+    /// Returns the number of lines on the hull
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.hull.len()
+    }
+
+    /// Returns [`true`] if no line has been inserted
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.hull.is_empty()
+    }
+
+    // Returns whether `m` is never strictly above both `l` and `r`, given that `l.a < m.a < r.a`
+    fn useless(l: &Line<T>, m: &Line<T>, r: &Line<T>) -> bool {
+        intersect(l, m) >= intersect(m, r)
+    }
+
+    /// Inserts the line $y = a x + b$
+    ///
+    /// Complexity: amortized $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::MonotoneCht;
+    ///
+    /// let mut cht = MonotoneCht::new_max();
+    /// cht.add_line(0, 0);
+    /// cht.add_line(1, -1);
+    /// cht.add_line(-1, -1);
+    /// assert_eq!(cht.query(3), Some(2));
+    /// assert_eq!(cht.query(0), Some(0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `a` is strictly between the slopes of two inserted lines.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::li_chao::MonotoneCht;
+    ///
+    /// let mut cht = MonotoneCht::new_max();
+    /// cht.add_line(1, 0);
+    /// cht.add_line(3, 0);
+    /// cht.add_line(2, 0);
+    /// ```
+    pub fn add_line(&mut self, a: T, b: T) {
+        let line = Line::new(a, b).orient(self.min);
+
+        let (Some(first), Some(last)) = (self.hull.front(), self.hull.back()) else {
+            self.hull.push_back(line);
+            return;
+        };
+        debug_assert!(line.a <= first.a || last.a <= line.a);
+
+        if last.a <= line.a {
+            if last.a == line.a {
+                if last.b >= line.b {
+                    return;
+                }
+                self.hull.pop_back();
+            }
+            while self.hull.len() >= 2 {
+                let n = self.hull.len();
+                if !Self::useless(&self.hull[n - 2], &self.hull[n - 1], &line) {
+                    break;
+                }
+                self.hull.pop_back();
+            }
+            self.hull.push_back(line);
+        } else {
+            if first.a == line.a {
+                if first.b >= line.b {
+                    return;
+                }
+                self.hull.pop_front();
+            }
+            while self.hull.len() >= 2 {
+                if !Self::useless(&line, &self.hull[0], &self.hull[1]) {
+                    break;
+                }
+                self.hull.pop_front();
+            }
+            self.hull.push_front(line);
+        }
+    }
+
+    /// Returns the maximum (or minimum) value at `x` among the inserted lines,
+    /// or [`None`] if no line has been inserted
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::MonotoneCht;
+    ///
+    /// let mut cht = MonotoneCht::new_min();
+    /// assert_eq!(cht.query(0), None);
+    /// cht.add_line(2, 0);
+    /// cht.add_line(-1, 3);
+    /// assert_eq!(cht.query(0), Some(0));
+    /// assert_eq!(cht.query(2), Some(1));
+    /// ```
+    pub fn query(&self, x: T) -> Option<T> {
+        // The first line of the hull not strictly below the next one
+        let (mut lo, mut hi) = (0, self.hull.len().checked_sub(1)?);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.hull[mid].eval(x) >= self.hull[mid + 1].eval(x) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        Some(orient(self.hull[lo].eval(x), self.min))
+    }
+
+    /// Returns the maximum (or minimum) value at `x` among the inserted lines,
+    /// or [`None`] if no line has been inserted.
+    ///
+    /// `x` must not be smaller than the argument of the previous calls to this method:
+    /// lines that cannot be optimal anymore are discarded.
+    ///
+    /// Complexity: amortized $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::MonotoneCht;
+    ///
+    /// let mut cht = MonotoneCht::new_max();
+    /// cht.add_line(-1, 0);
+    /// cht.add_line(1, 0);
+    /// assert_eq!(cht.query_monotone(-3), Some(3));
+    /// assert_eq!(cht.query_monotone(5), Some(5));
+    /// assert_eq!(cht.len(), 1);
+    /// ```
+    pub fn query_monotone(&mut self, x: T) -> Option<T> {
+        while self.hull.len() >= 2 && self.hull[0].eval(x) <= self.hull[1].eval(x) {
+            self.hull.pop_front();
+        }
+        self.hull.front().map(|line| orient(line.eval(x), self.min))
+    }
+}
+
+/// Convex hull trick for lines inserted in any order
+///
+/// Stores the upper envelope of the lines ordered by slope, together with the
+/// range of integers on which each of them is optimal, as in the `LineContainer` of KACTL.
+///
+/// `T` must be [`LineInt`]
+#[derive(Clone, Debug)]
+pub struct LineContainer<T> {
+    // For each slope of the hull, the intercept and the last integer at which the line is optimal
+    lines: BTreeMap<T, (T, T)>,
+    // The pairs (last integer at which the line is optimal, slope)
+    ends: BTreeSet<(T, T)>,
+    min: bool,
+}
+
+impl<T: LineInt> LineContainer<T> {
+    /// Creates an empty container answering maximum queries
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn new_max() -> Self {
+        LineContainer {
+            lines: BTreeMap::new(),
+            ends: BTreeSet::new(),
+            min: false,
+        }
+    }
+
+    /// Creates an empty container answering minimum queries
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn new_min() -> Self {
+        LineContainer {
+            lines: BTreeMap::new(),
+            ends: BTreeSet::new(),
+            min: true,
+        }
+    }
+
+    /// Returns the number of lines on the hull
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns [`true`] if no line has been inserted
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    fn prev(&self, a: T) -> Option<Line<T>> {
+        self.lines
+            .range(..a)
+            .next_back()
+            .map(|(&a, &(b, _))| Line::new(a, b))
+    }
+
+    fn next(&self, a: T) -> Option<Line<T>> {
+        self.lines
+            .range(a..)
+            .nth(1)
+            .map(|(&a, &(b, _))| Line::new(a, b))
+    }
+
+    fn end(&self, a: T) -> T {
+        self.lines[&a].1
+    }
+
+    // Recomputes the end of the range of `line`, which must be on the hull
+    fn update_end(&mut self, line: &Line<T>) {
+        let end = match self.next(line.a) {
+            Some(next) => intersect(line, &next),
+            None => T::MAX,
+        };
+
+        let (_, old) = self.lines.insert(line.a, (line.b, end)).unwrap();
+        self.ends.remove(&(old, line.a));
+        self.ends.insert((end, line.a));
+    }
+
+    fn remove(&mut self, a: T) {
+        let (_, end) = self.lines.remove(&a).unwrap();
+        self.ends.remove(&(end, a));
+    }
+
+    /// Inserts the line $y = a x + b$
+    ///
+    /// Complexity: amortized $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::LineContainer;
+    ///
+    /// let mut hull = LineContainer::new_max();
+    /// hull.add_line(1, -1);
+    /// hull.add_line(-1, -1);
+    /// hull.add_line(0, 0);
+    /// hull.add_line(0, -5);
+    /// assert_eq!(hull.len(), 3);
+    /// assert_eq!(hull.query(3), Some(2));
+    /// assert_eq!(hull.query(0), Some(0));
+    /// ```
+    pub fn add_line(&mut self, a: T, b: T) {
+        let line = Line::new(a, b).orient(self.min);
+
+        match self.lines.get(&line.a) {
+            Some(&(b, _)) if b >= line.b => return,
+            Some(_) => self.remove(line.a),
+            None => {}
+        }
+
+        self.lines.insert(line.a, (line.b, T::MAX));
+        self.ends.insert((T::MAX, line.a));
+        self.update_end(&line);
+
+        while let Some(next) = self.next(line.a) {
+            if self.end(line.a) < self.end(next.a) {
+                break;
+            }
+            self.remove(next.a);
+            self.update_end(&line);
+        }
+
+        let Some(mut cur) = self.prev(line.a) else {
+            return;
+        };
+        self.update_end(&cur);
+        if self.end(cur.a) >= self.end(line.a) {
+            self.remove(line.a);
+            self.update_end(&cur);
+        }
+
+        while let Some(prev) = self.prev(cur.a) {
+            if self.end(prev.a) < self.end(cur.a) {
+                break;
+            }
+            self.remove(cur.a);
+            self.update_end(&prev);
+            cur = prev;
+        }
+    }
+
+    /// Returns the maximum (or minimum) value at `x` among the inserted lines,
+    /// or [`None`] if no line has been inserted
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::li_chao::LineContainer;
+    ///
+    /// let mut hull = LineContainer::new_min();
+    /// assert_eq!(hull.query(0), None);
+    /// hull.add_line(2, 0);
+    /// hull.add_line(-1, 3);
+    /// hull.add_line(0, 2);
+    /// assert_eq!(hull.query(0), Some(0));
+    /// assert_eq!(hull.query(1), Some(2));
+    /// assert_eq!(hull.query(5), Some(-2));
+    /// ```
+    pub fn query(&self, x: T) -> Option<T> {
+        let &(_, a) = self.ends.range((x, T::MIN)..).next()?;
+        let line = Line::new(a, self.lines[&a].0);
+        Some(orient(line.eval(x), self.min))
+    }
+}
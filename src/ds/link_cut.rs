@@ -0,0 +1,439 @@
+use crate::math::algebra::Monoid;
+
+const NONE: usize = usize::MAX;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    val: T,
+    // Folds of the splay subtree, in path order and in reversed path order
+    sum: T,
+    rsum: T,
+    children: [usize; 2],
+    parent: usize,
+    rev: bool,
+}
+
+/// Link-cut tree over a [monoid](https://en.wikipedia.org/wiki/Monoid) `T`
+///
+/// `T` must be [`Clone`] and [`Monoid`], but not necessarily commutative:
+/// path folds follow the order of the path.
+///
+/// Maintains a forest on vertices $[0, N)$ with a value on each vertex, under edge insertions and deletions.
+/// The trees are rooted, and the root of the tree containing a vertex can be changed with [`LinkCutTree::evert`].
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::link_cut::LinkCutTree;
+///
+/// let mut lct = LinkCutTree::from(&[1, 2, 3, 4, 5]);
+/// lct.link(0, 1);
+/// lct.link(1, 2);
+/// lct.link(1, 3);
+/// assert_eq!(lct.fold(2, 3), 9);
+/// assert!(!lct.connected(0, 4));
+///
+/// lct.cut(1, 3);
+/// lct.link(3, 4);
+/// lct.link(4, 0);
+/// assert_eq!(lct.fold(2, 3), 15);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LinkCutTree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+impl<T> LinkCutTree<T>
+where
+    T: Clone + Monoid,
+{
+    /// Builds a forest of `size` isolated vertices, with identity values
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    ///
+    /// let lct: LinkCutTree<i64> = LinkCutTree::new(10);
+    /// assert_eq!(lct.len(), 10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        Self::from(&vec![T::ID; size])
+    }
+
+    /// Builds a forest of isolated vertices with the given values
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    ///
+    /// let lct = LinkCutTree::from(&[1, 2, 3]);
+    /// assert_eq!(lct.get(1), 2);
+    /// ```
+    pub fn from(values: &[T]) -> Self {
+        let nodes = values
+            .iter()
+            .map(|val| Node {
+                val: val.clone(),
+                sum: val.clone(),
+                rsum: val.clone(),
+                children: [NONE; 2],
+                parent: NONE,
+                rev: false,
+            })
+            .collect();
+
+        LinkCutTree { nodes }
+    }
+
+    /// Returns the number of vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns [`true`] if the forest has no vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Returns true if v is the root of its splay tree
+    fn is_splay_root(&self, v: usize) -> bool {
+        let p = self.nodes[v].parent;
+        p == NONE || !self.nodes[p].children.contains(&v)
+    }
+
+    fn sums(&self, v: usize) -> (T, T) {
+        if v == NONE {
+            (T::ID, T::ID)
+        } else {
+            (self.nodes[v].sum.clone(), self.nodes[v].rsum.clone())
+        }
+    }
+
+    fn pull(&mut self, v: usize) {
+        let [l, r] = self.nodes[v].children;
+        let ((lsum, lrsum), (rsum, rrsum)) = (self.sums(l), self.sums(r));
+        let val = self.nodes[v].val.clone();
+        self.nodes[v].sum = lsum.op(val.clone()).op(rsum);
+        self.nodes[v].rsum = rrsum.op(val).op(lrsum);
+    }
+
+    // Reverses the path represented by the splay subtree of v
+    fn toggle(&mut self, v: usize) {
+        if v != NONE {
+            let node = &mut self.nodes[v];
+            node.children.swap(0, 1);
+            std::mem::swap(&mut node.sum, &mut node.rsum);
+            node.rev ^= true;
+        }
+    }
+
+    fn push(&mut self, v: usize) {
+        if self.nodes[v].rev {
+            self.nodes[v].rev = false;
+            let [l, r] = self.nodes[v].children;
+            self.toggle(l);
+            self.toggle(r);
+        }
+    }
+
+    fn rotate(&mut self, v: usize) {
+        let p = self.nodes[v].parent;
+        let g = self.nodes[p].parent;
+        let dir = usize::from(self.nodes[p].children[1] == v);
+
+        if !self.is_splay_root(p) {
+            let pdir = usize::from(self.nodes[g].children[1] == p);
+            self.nodes[g].children[pdir] = v;
+        }
+        self.nodes[v].parent = g;
+
+        let mid = self.nodes[v].children[dir ^ 1];
+        self.nodes[p].children[dir] = mid;
+        if mid != NONE {
+            self.nodes[mid].parent = p;
+        }
+
+        self.nodes[v].children[dir ^ 1] = p;
+        self.nodes[p].parent = v;
+        self.pull(p);
+        self.pull(v);
+    }
+
+    fn splay(&mut self, v: usize) {
+        let mut path = vec![v];
+        while !self.is_splay_root(*path.last().unwrap()) {
+            path.push(self.nodes[*path.last().unwrap()].parent);
+        }
+        for &u in path.iter().rev() {
+            self.push(u);
+        }
+
+        while !self.is_splay_root(v) {
+            let p = self.nodes[v].parent;
+            if !self.is_splay_root(p) {
+                let g = self.nodes[p].parent;
+                let zigzig = (self.nodes[g].children[1] == p) == (self.nodes[p].children[1] == v);
+                self.rotate(if zigzig { p } else { v });
+            }
+            self.rotate(v);
+        }
+    }
+
+    // Makes the root-to-v path preferred, with v at the root of its splay tree.
+    // Returns the last vertex where the path joined the previous preferred path.
+    fn access(&mut self, v: usize) -> usize {
+        let mut last = NONE;
+        let mut u = v;
+        while u != NONE {
+            self.splay(u);
+            self.nodes[u].children[1] = last;
+            self.pull(u);
+            last = u;
+            u = self.nodes[u].parent;
+        }
+        self.splay(v);
+        last
+    }
+
+    /// Returns the root of the tree containing `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    ///
+    /// let mut lct: LinkCutTree<i64> = LinkCutTree::new(3);
+    /// lct.link(1, 0);
+    /// assert_eq!(lct.root(1), 0);
+    /// lct.evert(1);
+    /// assert_eq!(lct.root(0), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid vertex.
+    pub fn root(&mut self, v: usize) -> usize {
+        self.access(v);
+        let mut u = v;
+        loop {
+            self.push(u);
+            match self.nodes[u].children[0] {
+                NONE => break,
+                l => u = l,
+            }
+        }
+        self.splay(u);
+        u
+    }
+
+    /// Makes `v` the root of its tree
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid vertex.
+    pub fn evert(&mut self, v: usize) {
+        self.access(v);
+        self.toggle(v);
+    }
+
+    /// Returns [`true`] if `u` and `v` are in the same tree
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+
+    /// Adds the edge between `u` and `v`, making `v` the parent of `u`, which becomes the root of its tree first.
+    /// Returns [`false`], without adding the edge, if they were already in the same tree.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    ///
+    /// let mut lct: LinkCutTree<i64> = LinkCutTree::new(3);
+    /// assert!(lct.link(0, 1));
+    /// assert!(lct.link(1, 2));
+    /// assert!(!lct.link(0, 2));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        self.evert(u);
+        if self.root(v) == u {
+            return false;
+        }
+        self.nodes[u].parent = v;
+        true
+    }
+
+    /// Removes the edge between `u` and `v`, after making `u` the root of its tree.
+    /// Returns [`false`] if there was no such edge.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    ///
+    /// let mut lct: LinkCutTree<i64> = LinkCutTree::new(3);
+    /// lct.link(0, 1);
+    /// lct.link(1, 2);
+    /// assert!(!lct.cut(0, 2));
+    /// assert!(lct.cut(2, 1));
+    /// assert!(!lct.connected(0, 2));
+    /// assert!(lct.connected(0, 1));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        self.evert(u);
+        self.access(v);
+        if self.nodes[v].children[0] != u {
+            return false;
+        }
+        self.push(u);
+        if self.nodes[u].children[1] != NONE {
+            return false;
+        }
+
+        self.nodes[v].children[0] = NONE;
+        self.nodes[u].parent = NONE;
+        self.pull(v);
+        true
+    }
+
+    /// Returns the lowest common ancestor of `u` and `v` for the current roots,
+    /// or [`None`] if they are in different trees
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    ///
+    /// let mut lct: LinkCutTree<i64> = LinkCutTree::new(5);
+    /// lct.link(1, 0);
+    /// lct.link(2, 1);
+    /// lct.link(3, 1);
+    /// assert_eq!(lct.lca(2, 3), Some(1));
+    /// assert_eq!(lct.lca(2, 4), None);
+    ///
+    /// lct.evert(2);
+    /// assert_eq!(lct.lca(0, 3), Some(1));
+    /// assert_eq!(lct.lca(1, 3), Some(1));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `u` or `v` is not a valid vertex.
+    pub fn lca(&mut self, u: usize, v: usize) -> Option<usize> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.access(u);
+        Some(self.access(v))
+    }
+
+    /// Returns the value of vertex `v`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid vertex.
+    pub fn get(&self, v: usize) -> T {
+        self.nodes[v].val.clone()
+    }
+
+    /// Replaces the value of vertex `v` with `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    ///
+    /// let mut lct = LinkCutTree::from(&[1, 2, 3]);
+    /// lct.link(0, 1);
+    /// lct.link(1, 2);
+    /// lct.set(1, 10);
+    /// assert_eq!(lct.fold(0, 2), 14);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `v` is not a valid vertex.
+    pub fn set(&mut self, v: usize, val: T) {
+        self.access(v);
+        self.nodes[v].val = val;
+        self.pull(v);
+    }
+
+    /// Returns the fold of the values on the path from `u` to `v`, in this order.
+    /// The root of their tree becomes `u`.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ amortized where:
+    /// - $N$ is the number of vertices.
+    ///
+    /// # Examples
+    ///
+    /// Composition of affine functions along a path:
+    ///
+    /// ```
+    /// use cp_library::ds::link_cut::LinkCutTree;
+    /// use cp_library::math::algebra::instances::Affine;
+    ///
+    /// let mut lct = LinkCutTree::from(&[Affine::new(2, 0), Affine::new(1, 3), Affine::new(5, 1)]);
+    /// lct.link(0, 1);
+    /// lct.link(2, 1);
+    /// assert_eq!(lct.fold(0, 2).eval(1), 26);
+    /// assert_eq!(lct.fold(2, 0).eval(1), 18);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `u` and `v` are in different trees.
+    pub fn fold(&mut self, u: usize, v: usize) -> T {
+        debug_assert!(self.connected(u, v));
+        self.evert(u);
+        self.access(v);
+        self.nodes[v].sum.clone()
+    }
+}
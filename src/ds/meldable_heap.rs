@@ -0,0 +1,658 @@
+use std::mem;
+use std::rc::Rc;
+
+const NONE: usize = usize::MAX;
+
+#[derive(Clone, Debug)]
+struct PairingNode<T> {
+    val: Option<T>,
+    child: usize,
+    next: usize,
+    // The previous sibling, or the parent for the first child
+    prev: usize,
+}
+
+/// Pairing heaps over a total order (`Ord`) `T`, returning the minimum element
+///
+/// All the heaps live in a single arena of nodes, so that they can be melded in $\mathcal{O}(1)$.
+/// Heaps are numbered in order of creation, starting from $0$ for the initial one.
+/// Each pushed element gets a handle, which stays valid across melds and can be used to decrease its key.
+/// Nodes are never deallocated.
+///
+/// # Example
+///
+/// Dijkstra's algorithm with $\mathcal{O}(1)$ amortized key decreases:
+///
+/// ```
+/// use cp_library::ds::meldable_heap::PairingHeap;
+///
+/// let adj: Vec<Vec<(usize, u64)>> = vec![vec![(1, 4), (2, 1)], vec![(3, 1)], vec![(1, 2), (3, 5)], vec![]];
+/// let mut heap = PairingHeap::new();
+/// let handles: Vec<_> = (0..4).map(|v| heap.push(0, (if v == 0 { 0 } else { u64::MAX }, v))).collect();
+/// let mut dist = vec![u64::MAX; 4];
+///
+/// while let Some((d, u)) = heap.pop(0) {
+///     dist[u] = d;
+///     for &(v, w) in &adj[u] {
+///         if dist[v] == u64::MAX && d + w < heap.get(handles[v]).0 {
+///             heap.decrease_key(0, handles[v], (d + w, v));
+///         }
+///     }
+/// }
+///
+/// assert_eq!(dist, vec![0, 3, 1, 4]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PairingHeap<T> {
+    nodes: Vec<PairingNode<T>>,
+    roots: Vec<usize>,
+    lens: Vec<usize>,
+}
+
+impl<T: Ord> PairingHeap<T> {
+    /// Creates an arena containing a single empty heap, numbered $0$
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let heap: PairingHeap<i32> = PairingHeap::new();
+    /// assert_eq!(heap.heaps(), 1);
+    /// ```
+    pub fn new() -> Self {
+        PairingHeap {
+            nodes: vec![],
+            roots: vec![NONE],
+            lens: vec![0],
+        }
+    }
+
+    /// Adds an empty heap to the arena, returning its number
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap: PairingHeap<i32> = PairingHeap::new();
+    /// assert_eq!(heap.add_heap(), 1);
+    /// assert_eq!(heap.heaps(), 2);
+    /// ```
+    pub fn add_heap(&mut self) -> usize {
+        self.roots.push(NONE);
+        self.lens.push(0);
+        self.roots.len() - 1
+    }
+
+    /// Returns the number of heaps in the arena
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn heaps(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns the number of elements in a given `heap`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self, heap: usize) -> usize {
+        self.lens[heap]
+    }
+
+    /// Returns [`true`] if a given `heap` is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self, heap: usize) -> bool {
+        self.lens[heap] == 0
+    }
+
+    fn val(&self, node: usize) -> &T {
+        self.nodes[node].val.as_ref().unwrap()
+    }
+
+    // Links two roots, returning the new root
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        if a == NONE {
+            return b;
+        }
+        if b == NONE {
+            return a;
+        }
+
+        let (a, b) = if self.val(b) < self.val(a) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        let child = self.nodes[a].child;
+        if child != NONE {
+            self.nodes[child].prev = b;
+        }
+        self.nodes[b].next = child;
+        self.nodes[b].prev = a;
+        self.nodes[a].child = b;
+        a
+    }
+
+    /// Pushes `val` in a given `heap`, returning its handle
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let h = heap.push(0, 5);
+    /// heap.push(0, 3);
+    /// assert_eq!(heap.peek(0), Some(&3));
+    /// assert_eq!(heap.get(h), &5);
+    /// ```
+    pub fn push(&mut self, heap: usize, val: T) -> usize {
+        self.nodes.push(PairingNode {
+            val: Some(val),
+            child: NONE,
+            next: NONE,
+            prev: NONE,
+        });
+        let node = self.nodes.len() - 1;
+
+        self.roots[heap] = self.link(self.roots[heap], node);
+        self.lens[heap] += 1;
+        node
+    }
+
+    /// Returns a reference to the minimum element of a given `heap`, or [`None`] if it is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn peek(&self, heap: usize) -> Option<&T> {
+        let root = self.roots[heap];
+        if root == NONE {
+            None
+        } else {
+            Some(self.val(root))
+        }
+    }
+
+    /// Returns a reference to the element with handle `handle`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// If the element has already been popped.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let h = heap.push(0, 5);
+    /// heap.pop(0);
+    /// heap.get(h);
+    /// ```
+    pub fn get(&self, handle: usize) -> &T {
+        self.val(handle)
+    }
+
+    /// Removes the minimum element of a given `heap` and returns it, or [`None`] if it is empty
+    ///
+    /// Complexity: amortized $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(0, 5);
+    /// heap.push(0, 3);
+    /// assert_eq!(heap.pop(0), Some(3));
+    /// assert_eq!(heap.pop(0), Some(5));
+    /// assert_eq!(heap.pop(0), None);
+    /// ```
+    pub fn pop(&mut self, heap: usize) -> Option<T> {
+        let root = self.roots[heap];
+        if root == NONE {
+            return None;
+        }
+
+        let mut children = vec![];
+        let mut child = self.nodes[root].child;
+        while child != NONE {
+            let next = self.nodes[child].next;
+            self.nodes[child].next = NONE;
+            self.nodes[child].prev = NONE;
+            children.push(child);
+            child = next;
+        }
+
+        let mut paired = Vec::with_capacity(children.len().div_ceil(2));
+        for pair in children.chunks(2) {
+            let second = pair.get(1).copied().unwrap_or(NONE);
+            paired.push(self.link(pair[0], second));
+        }
+        self.roots[heap] = paired
+            .into_iter()
+            .rev()
+            .fold(NONE, |acc, node| self.link(node, acc));
+
+        self.nodes[root].child = NONE;
+        self.lens[heap] -= 1;
+        self.nodes[root].val.take()
+    }
+
+    /// Replaces the element with handle `handle`, which must be in a given `heap`,
+    /// by `val`, which must not be greater
+    ///
+    /// Complexity: amortized $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(0, 3);
+    /// let h = heap.push(0, 5);
+    /// heap.decrease_key(0, h, 1);
+    /// assert_eq!(heap.pop(0), Some(1));
+    /// assert_eq!(heap.pop(0), Some(3));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `val` is greater than the current element.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let h = heap.push(0, 5);
+    /// heap.decrease_key(0, h, 6);
+    /// ```
+    pub fn decrease_key(&mut self, heap: usize, handle: usize, val: T) {
+        debug_assert!(&val <= self.val(handle));
+
+        self.nodes[handle].val = Some(val);
+        if handle == self.roots[heap] {
+            return;
+        }
+
+        let PairingNode { next, prev, .. } = self.nodes[handle];
+        if self.nodes[prev].child == handle {
+            self.nodes[prev].child = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+        if next != NONE {
+            self.nodes[next].prev = prev;
+        }
+        self.nodes[handle].next = NONE;
+        self.nodes[handle].prev = NONE;
+
+        self.roots[heap] = self.link(self.roots[heap], handle);
+    }
+
+    /// Moves all the elements of heap `other` into heap `heap`, leaving `other` empty.
+    ///
+    /// Handles of the moved elements stay valid.
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let b = heap.add_heap();
+    /// heap.push(0, 5);
+    /// let h = heap.push(b, 7);
+    /// heap.push(b, 3);
+    ///
+    /// heap.meld(0, b);
+    /// assert!(heap.is_empty(b));
+    /// heap.decrease_key(0, h, 1);
+    /// assert_eq!(heap.pop(0), Some(1));
+    /// assert_eq!(heap.pop(0), Some(3));
+    /// assert_eq!(heap.len(0), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `heap` and `other` are the same heap.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::meldable_heap::PairingHeap;
+    ///
+    /// let mut heap: PairingHeap<i32> = PairingHeap::new();
+    /// heap.meld(0, 0);
+    /// ```
+    pub fn meld(&mut self, heap: usize, other: usize) {
+        debug_assert!(heap != other);
+
+        let root = mem::replace(&mut self.roots[other], NONE);
+        self.roots[heap] = self.link(self.roots[heap], root);
+        self.lens[heap] += mem::take(&mut self.lens[other]);
+    }
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+type Link<T> = Option<Box<LeftistNode<T>>>;
+
+#[derive(Clone, Debug)]
+struct LeftistNode<T> {
+    val: T,
+    // Length of the right spine
+    rank: usize,
+    l: Link<T>,
+    r: Link<T>,
+}
+
+fn rank<T>(node: &Link<T>) -> usize {
+    node.as_ref().map_or(0, |node| node.rank)
+}
+
+// The recursion only descends the right spines, of logarithmic length
+fn merge<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (mut a, b) = match (a, b) {
+        (None, b) => return b,
+        (a, None) => return a,
+        (Some(a), Some(b)) if b.val < a.val => (b, a),
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    a.r = merge(a.r.take(), Some(b));
+    if rank(&a.l) < rank(&a.r) {
+        mem::swap(&mut a.l, &mut a.r);
+    }
+    a.rank = rank(&a.r) + 1;
+    Some(a)
+}
+
+/// Leftist heap over a total order (`Ord`) `T`, returning the minimum element
+///
+/// Unlike [`std::collections::BinaryHeap`], two heaps can be melded in logarithmic time.
+///
+/// # Examples
+///
+/// ```
+/// use cp_library::ds::meldable_heap::LeftistHeap;
+///
+/// let mut a = LeftistHeap::new();
+/// a.push(5);
+/// a.push(2);
+/// let mut b = LeftistHeap::new();
+/// b.push(3);
+///
+/// a.meld(b);
+/// assert_eq!(a.pop(), Some(2));
+/// assert_eq!(a.pop(), Some(3));
+/// assert_eq!(a.pop(), Some(5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LeftistHeap<T> {
+    root: Link<T>,
+    len: usize,
+}
+
+impl<T: Ord> LeftistHeap<T> {
+    /// Creates an empty heap
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn new() -> Self {
+        LeftistHeap { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the heap
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns [`true`] if the heap is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Pushes `val` in the heap
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements in the heap.
+    pub fn push(&mut self, val: T) {
+        let node = Box::new(LeftistNode {
+            val,
+            rank: 1,
+            l: None,
+            r: None,
+        });
+        self.root = merge(self.root.take(), Some(node));
+        self.len += 1;
+    }
+
+    /// Returns a reference to the minimum element, or [`None`] if the heap is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.val)
+    }
+
+    /// Removes the minimum element and returns it, or [`None`] if the heap is empty
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut node = self.root.take()?;
+        self.root = merge(node.l.take(), node.r.take());
+        self.len -= 1;
+        Some(node.val)
+    }
+
+    /// Moves all the elements of `other` into `self`
+    ///
+    /// Complexity: $\mathcal{O}(\log N + \log M)$ where:
+    /// - $N$ and $M$ are the numbers of elements in the heaps.
+    pub fn meld(&mut self, mut other: Self) {
+        self.root = merge(self.root.take(), other.root.take());
+        self.len += mem::take(&mut other.len);
+    }
+}
+
+impl<T: Ord> Default for LeftistHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Left spines can be linear in length, so dropping recursively could overflow the stack
+impl<T> Drop for LeftistHeap<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.l.take());
+            stack.extend(node.r.take());
+        }
+    }
+}
+
+type RcLink<T> = Option<Rc<PersistentNode<T>>>;
+
+#[derive(Debug)]
+struct PersistentNode<T> {
+    val: T,
+    rank: usize,
+    l: RcLink<T>,
+    r: RcLink<T>,
+}
+
+fn rc_rank<T>(node: &RcLink<T>) -> usize {
+    node.as_ref().map_or(0, |node| node.rank)
+}
+
+// Copies the nodes on the right spines, sharing everything else
+fn rc_merge<T: Ord + Clone>(a: &RcLink<T>, b: &RcLink<T>) -> RcLink<T> {
+    let (a, b) = match (a, b) {
+        (None, b) => return b.clone(),
+        (a, None) => return a.clone(),
+        (Some(a), Some(b)) if b.val < a.val => (b, a),
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    let (mut l, mut r) = (a.l.clone(), rc_merge(&a.r, &Some(b.clone())));
+    if rc_rank(&l) < rc_rank(&r) {
+        mem::swap(&mut l, &mut r);
+    }
+    Some(Rc::new(PersistentNode {
+        val: a.val.clone(),
+        rank: rc_rank(&r) + 1,
+        l,
+        r,
+    }))
+}
+
+/// Persistent leftist heap over a total order (`Ord`) `T`, returning the minimum element
+///
+/// Every operation returns a new heap and leaves the original one untouched,
+/// sharing all but $\mathcal{O}(\log N)$ nodes with it. Cloning a heap takes $\mathcal{O}(1)$.
+///
+/// `T` must be [`Ord`] and [`Clone`]
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::meldable_heap::PersistentLeftistHeap;
+///
+/// let a = PersistentLeftistHeap::new().push(5).push(2);
+/// let b = a.push(3);
+/// let c = b.meld(&a);
+///
+/// assert_eq!(a.len(), 2);
+/// assert_eq!(b.len(), 3);
+/// assert_eq!(c.len(), 5);
+///
+/// let (min, rest) = c.pop().unwrap();
+/// assert_eq!(min, 2);
+/// assert_eq!(rest.peek(), Some(&2));
+/// assert_eq!(c.peek(), Some(&2));
+/// ```
+#[derive(Debug)]
+pub struct PersistentLeftistHeap<T> {
+    root: RcLink<T>,
+    len: usize,
+}
+
+impl<T> Clone for PersistentLeftistHeap<T> {
+    fn clone(&self) -> Self {
+        PersistentLeftistHeap {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Ord + Clone> PersistentLeftistHeap<T> {
+    /// Creates an empty heap
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn new() -> Self {
+        PersistentLeftistHeap { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the heap
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns [`true`] if the heap is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the heap obtained by pushing `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements in the heap.
+    pub fn push(&self, val: T) -> Self {
+        let node = Some(Rc::new(PersistentNode {
+            val,
+            rank: 1,
+            l: None,
+            r: None,
+        }));
+        PersistentLeftistHeap {
+            root: rc_merge(&self.root, &node),
+            len: self.len + 1,
+        }
+    }
+
+    /// Returns a reference to the minimum element, or [`None`] if the heap is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.val)
+    }
+
+    /// Returns the minimum element together with the heap obtained by removing it,
+    /// or [`None`] if the heap is empty
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the number of elements in the heap.
+    pub fn pop(&self) -> Option<(T, Self)> {
+        let node = self.root.as_ref()?;
+        let rest = PersistentLeftistHeap {
+            root: rc_merge(&node.l, &node.r),
+            len: self.len - 1,
+        };
+        Some((node.val.clone(), rest))
+    }
+
+    /// Returns the heap containing the elements of both `self` and `other`
+    ///
+    /// Complexity: $\mathcal{O}(\log N + \log M)$ where:
+    /// - $N$ and $M$ are the numbers of elements in the heaps.
+    pub fn meld(&self, other: &Self) -> Self {
+        PersistentLeftistHeap {
+            root: rc_merge(&self.root, &other.root),
+            len: self.len + other.len,
+        }
+    }
+}
+
+impl<T: Ord + Clone> Default for PersistentLeftistHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// See the `Drop` implementation of `LeftistHeap`
+impl<T> Drop for PersistentLeftistHeap<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(node) {
+                stack.extend(node.l.take());
+                stack.extend(node.r.take());
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::binsearch::first_true;
+use crate::ds::sorted_vec::SortedVec;
+
+/// Merge sort tree over a total order (`Ord`) `T`
+///
+/// A segment tree whose nodes are the [`SortedVec`]s of the elements in their range,
+/// answering order statistics queries on subarrays.
+/// Optionally, fractional cascading can be enabled to speed up counting queries.
+///
+/// # Example
+///
+/// Offline rectangle counting, together with a [`CoordinateCompressor`](crate::ds::coord_comp::CoordinateCompressor)-like
+/// sorted vector of abscissas:
+///
+/// ```
+/// use cp_library::ds::merge_sort_tree::MergeSortTree;
+/// use cp_library::ds::sorted_vec::SortedVec;
+///
+/// let mut points = vec![(1, 5), (3, 2), (4, 4), (6, 1), (7, 3)];
+/// points.sort();
+///
+/// let xs = SortedVec::from_sorted_slice(&points.iter().map(|p| p.0).collect::<Vec<_>>());
+/// let tree = MergeSortTree::from(&points.iter().map(|p| p.1).collect::<Vec<_>>());
+///
+/// // Points with 2 <= x < 7 and 2 <= y < 5
+/// let (l, r) = (xs.lower_bound(&2), xs.lower_bound(&7));
+/// assert_eq!(tree.count(l, r, &2, &5), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MergeSortTree<T> {
+    nodes: Vec<SortedVec<T>>,
+    // For each node and each prefix of its sorted vector, how many elements come from the left child
+    cascade: Option<Vec<Vec<usize>>>,
+    size: usize,
+    len: usize,
+}
+
+impl<T> MergeSortTree<T>
+where
+    T: Ord + Clone,
+{
+    /// Builds a merge sort tree from a slice of `T`.
+    ///
+    /// Complexity: $\mathcal{O}(N \log N)$ comparisons and copies where:
+    /// - $N$ is the length of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::from(&[3, 1, 4, 1, 5]);
+    /// ```
+    pub fn from(array: &[T]) -> Self {
+        let len = array.len();
+        let size = len.next_power_of_two();
+        let mut nodes = vec![SortedVec::from(vec![]); 2 * size];
+
+        for (i, val) in array.iter().enumerate() {
+            nodes[i + size] = SortedVec::from(vec![val.clone()]);
+        }
+        for i in (1..size).rev() {
+            let mut node = nodes[2 * i].clone();
+            node.merge(nodes[2 * i + 1].clone());
+            nodes[i] = node;
+        }
+
+        MergeSortTree {
+            nodes,
+            cascade: None,
+            size,
+            len,
+        }
+    }
+
+    /// Builds a merge sort tree from a slice of `T`, with fractional cascading.
+    ///
+    /// Counting queries then require a single binary search,
+    /// at the cost of $\mathcal{O}(N \log N)$ additional memory.
+    ///
+    /// Complexity: $\mathcal{O}(N \log N)$ comparisons and copies where:
+    /// - $N$ is the length of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::with_cascading(&[3, 1, 4, 1, 5]);
+    /// assert_eq!(tree.count(1, 4, &1, &4), 2);
+    /// ```
+    pub fn with_cascading(array: &[T]) -> Self {
+        let mut tree = Self::from(array);
+        let mut cascade = vec![vec![]; tree.size];
+
+        for (i, from_left) in cascade.iter_mut().enumerate().skip(1) {
+            let (node, left) = (&tree.nodes[i], &tree.nodes[2 * i]);
+            from_left.reserve(node.len() + 1);
+            from_left.push(0);
+
+            let mut j = 0;
+            for val in node.iter() {
+                if j < left.len() && &left[j] == val {
+                    j += 1;
+                }
+                from_left.push(j);
+            }
+        }
+
+        tree.cascade = Some(cascade);
+        tree
+    }
+
+    /// Returns the length of the underlying array
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns [`true`] if the underlying array is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements in the range $[l, r)$ whose value is in $[lo, hi)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ comparisons, or $\mathcal{O}(\log N)$ with fractional cascading, where:
+    /// - $N$ is the length of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::from(&[3, 1, 4, 1, 5, 9, 2, 6]);
+    /// assert_eq!(tree.count(1, 6, &1, &5), 3);
+    /// assert_eq!(tree.count(0, 8, &5, &10), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range:
+    /// ```should_panic
+    /// use cp_library::ds::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::from(&[3, 1, 4, 1, 5]);
+    /// tree.count(3, 2, &1, &4);
+    /// ```
+    pub fn count(&self, l: usize, r: usize, lo: &T, hi: &T) -> usize {
+        debug_assert!(l <= r && r <= self.len);
+
+        if lo >= hi {
+            return 0;
+        }
+
+        match &self.cascade {
+            Some(cascade) => {
+                let root = &self.nodes[1];
+                let (plo, phi) = (root.lower_bound(lo), root.lower_bound(hi));
+                self.count_cascading(cascade, 1, 0, self.size, l, r, plo, phi)
+            }
+            None => {
+                self.sum_nodes(l, r, |node| node.lower_bound(hi))
+                    - self.sum_nodes(l, r, |node| node.lower_bound(lo))
+            }
+        }
+    }
+
+    // Sums `f` over the nodes covering [l, r)
+    fn sum_nodes<F>(&self, l: usize, r: usize, f: F) -> usize
+    where
+        F: Fn(&SortedVec<T>) -> usize,
+    {
+        let mut ans = 0;
+        let (mut l, mut r) = (l + self.size, r + self.size);
+        while l < r {
+            if (l & 1) == 1 {
+                ans += f(&self.nodes[l]);
+                l += 1;
+            }
+            if (r & 1) == 1 {
+                r -= 1;
+                ans += f(&self.nodes[r]);
+            }
+
+            (l, r) = (l >> 1, r >> 1);
+        }
+        ans
+    }
+
+    // `plo` and `phi` are the lower bounds of the queried values in the node
+    #[allow(clippy::too_many_arguments)]
+    fn count_cascading(
+        &self,
+        cascade: &[Vec<usize>],
+        k: usize,
+        nl: usize,
+        nr: usize,
+        l: usize,
+        r: usize,
+        plo: usize,
+        phi: usize,
+    ) -> usize {
+        if r <= nl || nr <= l || plo == phi {
+            return 0;
+        }
+        if l <= nl && nr <= r {
+            return phi - plo;
+        }
+
+        let mid = (nl + nr) / 2;
+        let (llo, lhi) = (cascade[k][plo], cascade[k][phi]);
+        self.count_cascading(cascade, 2 * k, nl, mid, l, r, llo, lhi)
+            + self.count_cascading(cascade, 2 * k + 1, mid, nr, l, r, plo - llo, phi - lhi)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest element in the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log^3 N)$ comparisons where:
+    /// - $N$ is the length of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::from(&[3, 1, 4, 1, 5, 9, 2, 6]);
+    /// assert_eq!(tree.kth_smallest(2, 7, 0), 1);
+    /// assert_eq!(tree.kth_smallest(2, 7, 3), 5);
+    /// assert_eq!(tree.kth_smallest(2, 7, 4), 9);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range, or `k` is not smaller than $r - l$.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::from(&[3, 1, 4, 1, 5]);
+    /// tree.kth_smallest(1, 3, 2);
+    /// ```
+    pub fn kth_smallest(&self, l: usize, r: usize, k: usize) -> T {
+        debug_assert!(l <= r && r <= self.len && k < r - l);
+
+        let root = &self.nodes[1];
+        let idx = first_true(0, root.len(), |i| {
+            self.sum_nodes(l, r, |node| node.upper_bound(&root[i])) > k
+        });
+        root[idx].clone()
+    }
+}
@@ -0,0 +1,525 @@
+/// State maintained by [`mo`] over a sliding window $[l, r)$ of an array
+///
+/// Only [`MoState::add`], [`MoState::remove`] and [`MoState::answer`] must be implemented:
+/// the directional methods default to them, and should be overridden when
+/// the side from which an element enters or leaves the window matters.
+pub trait MoState {
+    /// The answer to a query
+    type Answer;
+
+    /// Inserts the element at index `idx` in the window
+    fn add(&mut self, idx: usize);
+
+    /// Removes the element at index `idx` from the window
+    fn remove(&mut self, idx: usize);
+
+    /// Returns the answer for the current window
+    fn answer(&mut self) -> Self::Answer;
+
+    /// Inserts the element at index `idx`, which becomes the first one of the window
+    fn add_left(&mut self, idx: usize) {
+        self.add(idx);
+    }
+
+    /// Inserts the element at index `idx`, which becomes the last one of the window
+    fn add_right(&mut self, idx: usize) {
+        self.add(idx);
+    }
+
+    /// Removes the element at index `idx`, which is the first one of the window
+    fn remove_left(&mut self, idx: usize) {
+        self.remove(idx);
+    }
+
+    /// Removes the element at index `idx`, which is the last one of the window
+    fn remove_right(&mut self, idx: usize) {
+        self.remove(idx);
+    }
+}
+
+/// State maintained by [`rollback_mo`] over a sliding window of an array,
+/// which only needs to support insertions
+pub trait RollbackMoState {
+    /// The answer to a query
+    type Answer;
+
+    /// Inserts the element at index `idx`, which becomes the first one of the window
+    fn add_left(&mut self, idx: usize);
+
+    /// Inserts the element at index `idx`, which becomes the last one of the window
+    fn add_right(&mut self, idx: usize);
+
+    /// Returns the answer for the current window
+    fn answer(&mut self) -> Self::Answer;
+
+    /// Empties the window
+    ///
+    /// It is called once for each block of queries, that is $\mathcal{O}(\sqrt Q)$ times.
+    fn reset(&mut self);
+
+    /// Remembers the current state, discarding the previously remembered one
+    fn snapshot(&mut self);
+
+    /// Restores the state remembered by the last call to [`RollbackMoState::snapshot`]
+    fn rollback(&mut self);
+}
+
+// Position of (x, y) along the Hilbert curve filling the square of side 2^log
+fn hilbert_order(x: usize, y: usize, log: u32) -> u64 {
+    let n = 1u64 << log;
+    let (mut x, mut y) = (x as u64, y as u64);
+    let mut d = 0;
+
+    let mut s = n >> 1;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                (x, y) = (n - 1 - x, n - 1 - y);
+            }
+            (x, y) = (y, x);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+// Sorts the queries along the Hilbert curve
+fn hilbert_sort(queries: &[(usize, usize)]) -> Vec<usize> {
+    let max = queries.iter().map(|&(l, r)| l.max(r)).max().unwrap_or(0);
+    let log = (max + 1).next_power_of_two().trailing_zeros();
+
+    let keys: Vec<_> = queries
+        .iter()
+        .map(|&(l, r)| hilbert_order(l, r, log))
+        .collect();
+    let mut order: Vec<_> = (0..queries.len()).collect();
+    order.sort_by_key(|&i| keys[i]);
+    order
+}
+
+// Moves the window [l, r) to [ql, qr), never making it of negative length
+fn move_window<S: MoState>(state: &mut S, window: &mut (usize, usize), ql: usize, qr: usize) {
+    let (l, r) = window;
+    while *l > ql {
+        *l -= 1;
+        state.add_left(*l);
+    }
+    while *r < qr {
+        state.add_right(*r);
+        *r += 1;
+    }
+    while *l < ql {
+        state.remove_left(*l);
+        *l += 1;
+    }
+    while *r > qr {
+        *r -= 1;
+        state.remove_right(*r);
+    }
+}
+
+/// Answers offline the queries on the ranges $[l, r)$ in `queries` with Mo's algorithm,
+/// visiting them in Hilbert curve order.
+/// Returns the answers in the order of the queries.
+///
+/// The window starts empty, and `state` must represent an empty window.
+///
+/// Complexity: $\mathcal{O}(N \sqrt Q)$ calls to the methods of `state` where:
+/// - $N$ is the length of the array;
+/// - $Q$ is the number of queries.
+///
+/// # Examples
+///
+/// Number of distinct values in ranges:
+///
+/// ```
+/// use cp_library::ds::mo::{mo, MoState};
+///
+/// struct Distinct<'a> {
+///     arr: &'a [usize],
+///     count: Vec<usize>,
+///     distinct: usize,
+/// }
+///
+/// impl MoState for Distinct<'_> {
+///     type Answer = usize;
+///
+///     fn add(&mut self, idx: usize) {
+///         self.count[self.arr[idx]] += 1;
+///         if self.count[self.arr[idx]] == 1 {
+///             self.distinct += 1;
+///         }
+///     }
+///
+///     fn remove(&mut self, idx: usize) {
+///         self.count[self.arr[idx]] -= 1;
+///         if self.count[self.arr[idx]] == 0 {
+///             self.distinct -= 1;
+///         }
+///     }
+///
+///     fn answer(&mut self) -> usize {
+///         self.distinct
+///     }
+/// }
+///
+/// let arr = [1, 2, 1, 3, 2, 2];
+/// let mut state = Distinct { arr: &arr, count: vec![0; 4], distinct: 0 };
+/// let ans = mo(&[(0, 3), (2, 6), (4, 6), (1, 1)], &mut state);
+/// assert_eq!(ans, vec![2, 3, 1, 0]);
+/// ```
+///
+/// # Panics
+///
+/// Only in debug builds, if a query is not a valid range.
+pub fn mo<S: MoState>(queries: &[(usize, usize)], state: &mut S) -> Vec<S::Answer> {
+    debug_assert!(queries.iter().all(|&(l, r)| l <= r));
+
+    let mut answers: Vec<_> = queries.iter().map(|_| None).collect();
+    let mut window = (0, 0);
+
+    for i in hilbert_sort(queries) {
+        let (l, r) = queries[i];
+        move_window(state, &mut window, l, r);
+        answers[i] = Some(state.answer());
+    }
+
+    answers.into_iter().map(Option::unwrap).collect()
+}
+
+/// Answers offline the queries on the ranges $[l, r)$ in `queries` with Mo's algorithm,
+/// using only insertions and rollbacks.
+/// Returns the answers in the order of the queries.
+///
+/// Useful when removing elements is hard, e.g. when maintaining maxima.
+///
+/// Complexity: $\mathcal{O}(N \sqrt Q)$ calls to the methods of `state` where:
+/// - $N$ is the length of the array;
+/// - $Q$ is the number of queries.
+///
+/// # Examples
+///
+/// Maximum distance between equal values in ranges:
+///
+/// ```
+/// use cp_library::ds::mo::{rollback_mo, RollbackMoState};
+///
+/// struct MaxGap<'a> {
+///     arr: &'a [usize],
+///     first: Vec<usize>,
+///     last: Vec<usize>,
+///     best: usize,
+///     history: Vec<(usize, usize, usize)>,
+///     saved: usize,
+/// }
+///
+/// impl MaxGap<'_> {
+///     fn insert(&mut self, idx: usize) {
+///         let v = self.arr[idx];
+///         self.history.push((v, self.first[v], self.last[v]));
+///         self.first[v] = self.first[v].min(idx);
+///         self.last[v] = self.last[v].max(idx);
+///         self.best = self.best.max(self.last[v] - self.first[v]);
+///     }
+/// }
+///
+/// impl RollbackMoState for MaxGap<'_> {
+///     type Answer = usize;
+///
+///     fn add_left(&mut self, idx: usize) {
+///         self.insert(idx);
+///     }
+///
+///     fn add_right(&mut self, idx: usize) {
+///         self.insert(idx);
+///     }
+///
+///     fn answer(&mut self) -> usize {
+///         self.best
+///     }
+///
+///     fn reset(&mut self) {
+///         self.history.clear();
+///         self.first.fill(usize::MAX);
+///         self.last.fill(0);
+///         self.best = 0;
+///     }
+///
+///     fn snapshot(&mut self) {
+///         self.history.clear();
+///         self.saved = self.best;
+///     }
+///
+///     fn rollback(&mut self) {
+///         while let Some((v, first, last)) = self.history.pop() {
+///             (self.first[v], self.last[v]) = (first, last);
+///         }
+///         self.best = self.saved;
+///     }
+/// }
+///
+/// let arr = [1, 2, 1, 3, 2, 1];
+/// let mut state = MaxGap {
+///     arr: &arr,
+///     first: vec![usize::MAX; 4],
+///     last: vec![0; 4],
+///     best: 0,
+///     history: vec![],
+///     saved: 0,
+/// };
+/// let ans = rollback_mo(&[(0, 3), (1, 5), (3, 4), (0, 6)], &mut state);
+/// assert_eq!(ans, vec![2, 3, 0, 5]);
+/// ```
+///
+/// # Panics
+///
+/// Only in debug builds, if a query is not a valid range.
+pub fn rollback_mo<S: RollbackMoState>(
+    queries: &[(usize, usize)],
+    state: &mut S,
+) -> Vec<S::Answer> {
+    debug_assert!(queries.iter().all(|&(l, r)| l <= r));
+
+    let len = queries.iter().map(|&(_, r)| r).max().unwrap_or(0);
+    let block = (len / queries.len().max(1).isqrt()).max(1);
+
+    let mut order: Vec<_> = (0..queries.len()).collect();
+    order.sort_by_key(|&i| (queries[i].0 / block, queries[i].1));
+
+    let mut answers: Vec<_> = queries.iter().map(|_| None).collect();
+    let mut current = None;
+    let mut r = 0;
+
+    for i in order {
+        let (ql, qr) = queries[i];
+        let end = (ql / block + 1) * block;
+
+        // Queries in the same block are sorted by right endpoint, so the ones
+        // ending in the block come first and find the window empty
+        if current != Some(end) {
+            state.reset();
+            (current, r) = (Some(end), end);
+        }
+
+        if qr <= end {
+            state.snapshot();
+            for idx in ql..qr {
+                state.add_right(idx);
+            }
+            answers[i] = Some(state.answer());
+            state.rollback();
+            continue;
+        }
+
+        while r < qr {
+            state.add_right(r);
+            r += 1;
+        }
+
+        state.snapshot();
+        for idx in (ql..end).rev() {
+            state.add_left(idx);
+        }
+        answers[i] = Some(state.answer());
+        state.rollback();
+    }
+
+    answers.into_iter().map(Option::unwrap).collect()
+}
+
+// Adapts a state over vertices to a window over the Euler tour,
+// where the vertices appearing exactly once are in the path
+struct Toggle<'a, S> {
+    state: &'a mut S,
+    tour: Vec<usize>,
+    inside: Vec<bool>,
+}
+
+impl<S: MoState> Toggle<'_, S> {
+    fn toggle(&mut self, idx: usize) {
+        let v = self.tour[idx];
+        if self.inside[v] {
+            self.state.remove(v);
+        } else {
+            self.state.add(v);
+        }
+        self.inside[v] = !self.inside[v];
+    }
+}
+
+impl<S: MoState> MoState for Toggle<'_, S> {
+    type Answer = ();
+
+    fn add(&mut self, idx: usize) {
+        self.toggle(idx);
+    }
+
+    fn remove(&mut self, idx: usize) {
+        self.toggle(idx);
+    }
+
+    fn answer(&mut self) {}
+}
+
+/// Answers offline the queries on the paths between the pairs of vertices in `queries`
+/// of the tree with adjacency lists `adj`, with Mo's algorithm on its Euler tour.
+/// Returns the answers in the order of the queries.
+///
+/// `state` receives the vertices of the path (including its endpoints) through
+/// [`MoState::add`] and [`MoState::remove`] only.
+///
+/// Complexity: $\mathcal{O}(N \sqrt Q)$ calls to the methods of `state` where:
+/// - $N$ is the number of vertices;
+/// - $Q$ is the number of queries.
+///
+/// # Examples
+///
+/// Number of distinct colors on paths:
+///
+/// ```
+/// use cp_library::ds::mo::{tree_mo, MoState};
+///
+/// struct Distinct<'a> {
+///     color: &'a [usize],
+///     count: Vec<usize>,
+///     distinct: usize,
+/// }
+///
+/// impl MoState for Distinct<'_> {
+///     type Answer = usize;
+///
+///     fn add(&mut self, v: usize) {
+///         self.count[self.color[v]] += 1;
+///         if self.count[self.color[v]] == 1 {
+///             self.distinct += 1;
+///         }
+///     }
+///
+///     fn remove(&mut self, v: usize) {
+///         self.count[self.color[v]] -= 1;
+///         if self.count[self.color[v]] == 0 {
+///             self.distinct -= 1;
+///         }
+///     }
+///
+///     fn answer(&mut self) -> usize {
+///         self.distinct
+///     }
+/// }
+///
+/// //     0
+/// //    / \
+/// //   1   2
+/// //  / \
+/// // 3   4
+/// let adj = vec![vec![1, 2], vec![0, 3, 4], vec![0], vec![1], vec![1]];
+/// let color = [0, 1, 0, 2, 1];
+/// let mut state = Distinct { color: &color, count: vec![0; 3], distinct: 0 };
+/// let ans = tree_mo(&adj, &[(3, 4), (3, 2), (0, 0), (4, 1)], &mut state);
+/// assert_eq!(ans, vec![2, 3, 1, 1]);
+/// ```
+///
+/// # Panics
+///
+/// Only in debug builds, if `adj` is not a tree.
+pub fn tree_mo<S: MoState>(
+    adj: &[Vec<usize>],
+    queries: &[(usize, usize)],
+    state: &mut S,
+) -> Vec<S::Answer> {
+    let n = adj.len();
+    let log = n.next_power_of_two().trailing_zeros() as usize + 1;
+
+    // Euler tour with entry and exit times, and binary lifting for the LCA
+    let mut tour = Vec::with_capacity(2 * n);
+    let (mut tin, mut tout) = (vec![0; n], vec![0; n]);
+    let mut up = vec![vec![0; n]; log];
+    let mut depth = vec![0; n];
+
+    let mut stack = vec![(0, 0, 0)];
+    while let Some((v, p, i)) = stack.pop() {
+        if i == 0 {
+            tin[v] = tour.len();
+            tour.push(v);
+            up[0][v] = p;
+        }
+        match adj[v].get(i) {
+            Some(&u) if (u == p && v != 0) || u == v => stack.push((v, p, i + 1)),
+            Some(&u) => {
+                stack.push((v, p, i + 1));
+                depth[u] = depth[v] + 1;
+                stack.push((u, v, 0));
+            }
+            None => {
+                tout[v] = tour.len();
+                tour.push(v);
+            }
+        }
+    }
+    debug_assert!(tour.len() == 2 * n);
+
+    for k in 1..log {
+        for v in 0..n {
+            up[k][v] = up[k - 1][up[k - 1][v]];
+        }
+    }
+    let lca = |mut u: usize, mut v: usize| {
+        if depth[u] < depth[v] {
+            (u, v) = (v, u);
+        }
+        for k in (0..log).rev() {
+            if depth[u] - depth[v] >= 1 << k {
+                u = up[k][u];
+            }
+        }
+        if u == v {
+            return u;
+        }
+        for k in (0..log).rev() {
+            if up[k][u] != up[k][v] {
+                (u, v) = (up[k][u], up[k][v]);
+            }
+        }
+        up[0][u]
+    };
+
+    // Each path becomes a range of the tour, plus possibly the LCA
+    let (ranges, extra): (Vec<_>, Vec<_>) = queries
+        .iter()
+        .map(|&(u, v)| {
+            let (u, v) = if tin[u] <= tin[v] { (u, v) } else { (v, u) };
+            let w = lca(u, v);
+            if w == u {
+                ((tin[u], tin[v] + 1), None)
+            } else {
+                ((tout[u], tin[v] + 1), Some(w))
+            }
+        })
+        .unzip();
+
+    let mut toggle = Toggle {
+        state,
+        tour,
+        inside: vec![false; n],
+    };
+    let mut answers: Vec<_> = queries.iter().map(|_| None).collect();
+    let mut window = (0, 0);
+
+    for i in hilbert_sort(&ranges) {
+        let (l, r) = ranges[i];
+        move_window(&mut toggle, &mut window, l, r);
+
+        if let Some(w) = extra[i] {
+            toggle.state.add(w);
+            answers[i] = Some(toggle.state.answer());
+            toggle.state.remove(w);
+        } else {
+            answers[i] = Some(toggle.state.answer());
+        }
+    }
+
+    answers.into_iter().map(Option::unwrap).collect()
+}
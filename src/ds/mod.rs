@@ -6,3 +6,69 @@ pub mod sorted_vec;
 
 /// Coordinate compressor
 pub mod coord_comp;
+
+/// Disjoint set union
+pub mod dsu;
+
+/// Persistent segment trees
+pub mod persistent_segtree;
+
+/// Dynamic segment trees
+pub mod dynamic_segtree;
+
+/// Segment tree beats
+pub mod segtree_beats;
+
+/// Treaps
+pub mod treap;
+
+/// Ordered sets with order statistics
+pub mod ordered_set;
+
+/// Fenwick trees
+pub mod fenwick;
+
+/// Wavelet matrices
+pub mod wavelet_matrix;
+
+/// Merge sort trees
+pub mod merge_sort_tree;
+
+/// Sliding window aggregation
+pub mod swag;
+
+/// Li Chao trees and convex hull tricks
+pub mod li_chao;
+
+/// Binary tries
+pub mod binary_trie;
+
+/// Meldable heaps
+pub mod meldable_heap;
+
+/// Interval sets and range maps
+pub mod interval_set;
+
+/// Mo's algorithm
+pub mod mo;
+
+/// Square root decompositions
+pub mod sqrt_decomposition;
+
+/// Fast integer sets
+pub mod fast_set;
+
+/// Dynamic bitsets
+pub mod bitset;
+
+/// Link-cut trees
+pub mod link_cut;
+
+/// Euler tour trees and dynamic connectivity
+pub mod euler_tour_tree;
+
+/// Offline dynamic connectivity
+pub mod offline_connectivity;
+
+/// Persistent arrays and disjoint set unions
+pub mod persistent_array;
@@ -0,0 +1,223 @@
+use crate::ds::dsu::RollbackDsu;
+use std::collections::HashMap;
+
+/// State of the graph at the time of a query, as answered by [`OfflineConnectivity::solve`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GraphState {
+    /// The number of connected components
+    pub components: usize,
+    /// Whether the graph is bipartite
+    pub bipartite: bool,
+}
+
+/// Offline dynamic connectivity, through divide and conquer over time
+///
+/// Edge insertions, edge deletions and queries are recorded first, then all queries are answered at once:
+/// the lifetime of each edge is split over the nodes of a segment tree indexed by queries,
+/// with the same layout as [`SegTree`](crate::ds::segtree::SegTree),
+/// and a depth-first traversal of the tree adds the edges of each node to a [`RollbackDsu`] and removes them on the way back.
+/// Multiple edges and self-loops are allowed.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::offline_connectivity::{GraphState, OfflineConnectivity};
+///
+/// let mut graph = OfflineConnectivity::new(3);
+/// graph.insert(0, 1);
+/// graph.insert(1, 2);
+/// graph.query();
+/// graph.insert(2, 0);
+/// graph.query();
+/// graph.remove(0, 1);
+/// graph.remove(1, 2);
+/// graph.query();
+///
+/// assert_eq!(
+///     graph.solve(),
+///     vec![
+///         GraphState { components: 1, bipartite: true },
+///         GraphState { components: 1, bipartite: false },
+///         GraphState { components: 2, bipartite: true },
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct OfflineConnectivity {
+    size: usize,
+    // Times at which the currently present copies of each edge were inserted
+    open: HashMap<(usize, usize), Vec<usize>>,
+    // Edges together with the range of queries during which they are present
+    lifetimes: Vec<(usize, usize, usize, usize)>,
+    queries: usize,
+}
+
+impl OfflineConnectivity {
+    /// Creates a graph of `size` vertices and no edges, with no queries
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::offline_connectivity::OfflineConnectivity;
+    ///
+    /// let mut graph = OfflineConnectivity::new(10);
+    /// graph.query();
+    /// assert_eq!(graph.solve()[0].components, 10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        OfflineConnectivity {
+            size,
+            open: HashMap::new(),
+            lifetimes: vec![],
+            queries: 0,
+        }
+    }
+
+    /// Returns the number of vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns [`true`] if the graph has no vertices
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the number of queries recorded so far
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn queries(&self) -> usize {
+        self.queries
+    }
+
+    /// Adds an edge between `u` and `v`
+    ///
+    /// Complexity: $\mathcal{O}(1)$ expected
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `u` or `v` is not a valid vertex.
+    pub fn insert(&mut self, u: usize, v: usize) {
+        debug_assert!(u < self.size && v < self.size);
+        self.open
+            .entry((u.min(v), u.max(v)))
+            .or_default()
+            .push(self.queries);
+    }
+
+    /// Removes an edge between `u` and `v`
+    ///
+    /// Complexity: $\mathcal{O}(1)$ expected
+    ///
+    /// # Panics
+    ///
+    /// If there is no edge between `u` and `v`.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::offline_connectivity::OfflineConnectivity;
+    ///
+    /// let mut graph = OfflineConnectivity::new(3);
+    /// graph.insert(0, 1);
+    /// graph.remove(1, 2);
+    /// ```
+    pub fn remove(&mut self, u: usize, v: usize) {
+        let key = (u.min(v), u.max(v));
+        let start = self
+            .open
+            .get_mut(&key)
+            .and_then(|times| times.pop())
+            .expect("no such edge");
+        if start < self.queries {
+            self.lifetimes.push((u, v, start, self.queries));
+        }
+    }
+
+    /// Records a query on the current state of the graph.
+    /// Returns its index in the answers of [`OfflineConnectivity::solve`].
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn query(&mut self) -> usize {
+        self.queries += 1;
+        self.queries - 1
+    }
+
+    /// Answers all the recorded queries, in order
+    ///
+    /// Complexity: $\mathcal{O}(N + Q + M \log Q \log N)$ where:
+    /// - $N$ is the number of vertices;
+    /// - $Q$ is the number of queries;
+    /// - $M$ is the number of edge insertions.
+    pub fn solve(&self) -> Vec<GraphState> {
+        let q = self.queries;
+        let mut nodes = vec![vec![]; 2 * q];
+
+        let still_open = self
+            .open
+            .iter()
+            .flat_map(|(&(u, v), times)| times.iter().map(move |&start| (u, v, start, q)));
+        for (u, v, l, r) in self.lifetimes.iter().copied().chain(still_open) {
+            let (mut l, mut r) = (l + q, r + q);
+            while l < r {
+                if l & 1 == 1 {
+                    nodes[l].push((u, v));
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    nodes[r].push((u, v));
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+
+        let mut answers = vec![None; q];
+        if q > 0 {
+            let mut dsu = RollbackDsu::new(self.size);
+            // Vertex v + N stands for the opposite side of v
+            let mut parity = RollbackDsu::new(2 * self.size);
+            self.dfs(1, true, &nodes, &mut dsu, &mut parity, &mut answers);
+        }
+        answers.into_iter().map(Option::unwrap).collect()
+    }
+
+    fn dfs(
+        &self,
+        node: usize,
+        bipartite: bool,
+        nodes: &[Vec<(usize, usize)>],
+        dsu: &mut RollbackDsu,
+        parity: &mut RollbackDsu,
+        answers: &mut [Option<GraphState>],
+    ) {
+        let n = self.size;
+        let (dsu_snap, parity_snap) = (dsu.snapshot(), parity.snapshot());
+
+        let mut bipartite = bipartite;
+        for &(u, v) in &nodes[node] {
+            dsu.unite(u, v);
+            parity.unite(u, v + n);
+            parity.unite(v, u + n);
+            bipartite &= !parity.same(u, u + n);
+        }
+
+        if node >= answers.len() {
+            answers[node - answers.len()] = Some(GraphState {
+                components: dsu.components(),
+                bipartite,
+            });
+        } else {
+            self.dfs(2 * node, bipartite, nodes, dsu, parity, answers);
+            self.dfs(2 * node + 1, bipartite, nodes, dsu, parity, answers);
+        }
+
+        dsu.rollback(dsu_snap);
+        parity.rollback(parity_snap);
+    }
+}
@@ -0,0 +1,480 @@
+use crate::ds::treap::xorshift;
+
+const SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    val: T,
+    size: usize,
+    priority: u64,
+    l: Link<T>,
+    r: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn pull(&mut self) {
+        self.size = size(&self.l) + 1 + size(&self.r);
+    }
+}
+
+fn size<T>(t: &Link<T>) -> usize {
+    t.as_ref().map_or(0, |node| node.size)
+}
+
+fn merge<T>(a: Link<T>, b: Link<T>) -> Link<T> {
+    match (a, b) {
+        (None, t) | (t, None) => t,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority > b.priority {
+                a.r = merge(a.r.take(), Some(b));
+                a.pull();
+                Some(a)
+            } else {
+                b.l = merge(Some(a), b.l.take());
+                b.pull();
+                Some(b)
+            }
+        }
+    }
+}
+
+// Splits the tree into the prefix of elements satisfying `left` and the rest
+fn split<T, F: Fn(&T) -> bool>(t: Link<T>, left: &F) -> (Link<T>, Link<T>) {
+    match t {
+        None => (None, None),
+        Some(mut node) => {
+            if left(&node.val) {
+                let (l, r) = split(node.r.take(), left);
+                node.r = l;
+                node.pull();
+                (Some(node), r)
+            } else {
+                let (l, r) = split(node.l.take(), left);
+                node.l = r;
+                node.pull();
+                (l, Some(node))
+            }
+        }
+    }
+}
+
+/// An ordered multiset over a total order (`Ord`) `T`, with order statistics
+///
+/// Implemented as a treap with subtree sizes.
+#[derive(Clone, Debug)]
+pub struct OrderedMultiset<T> {
+    root: Link<T>,
+    seed: u64,
+}
+
+impl<T> OrderedMultiset<T>
+where
+    T: Ord,
+{
+    /// Builds an empty multiset
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set: OrderedMultiset<i32> = OrderedMultiset::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        OrderedMultiset {
+            root: None,
+            seed: SEED,
+        }
+    }
+
+    /// Returns the number of elements in the multiset, counting repetitions
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns [`true`] if the multiset contains no elements
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Inserts an occurrence of `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let mut set = OrderedMultiset::new();
+    /// set.insert(3);
+    /// set.insert(1);
+    /// set.insert(3);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![&1, &3, &3]);
+    /// ```
+    pub fn insert(&mut self, v: T) {
+        let (l, r) = split(self.root.take(), &|x| x < &v);
+        let node = Box::new(Node {
+            val: v,
+            size: 1,
+            priority: xorshift(&mut self.seed),
+            l: None,
+            r: None,
+        });
+        self.root = merge(merge(l, Some(node)), r);
+    }
+
+    /// Removes an occurrence of `v`.
+    /// Returns [`false`] if `v` was not present.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let mut set = OrderedMultiset::from_iter([1, 3, 3]);
+    /// assert!(set.remove_one(&3));
+    /// assert!(!set.remove_one(&2));
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![&1, &3]);
+    /// ```
+    pub fn remove_one(&mut self, v: &T) -> bool {
+        let (l, r) = split(self.root.take(), &|x| x < v);
+        let (m, r) = split(r, &|x| x == v);
+
+        let found = m.is_some();
+        let m = m.and_then(|node| merge(node.l, node.r));
+        self.root = merge(merge(l, m), r);
+
+        found
+    }
+
+    /// Removes all occurrences of `v`, returning how many there were
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let mut set = OrderedMultiset::from_iter([1, 3, 3]);
+    /// assert_eq!(set.remove_all(&3), 2);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn remove_all(&mut self, v: &T) -> usize {
+        let (l, r) = split(self.root.take(), &|x| x < v);
+        let (m, r) = split(r, &|x| x == v);
+        self.root = merge(l, r);
+        size(&m)
+    }
+
+    /// Returns the number of elements strictly smaller than `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([1, 3, 3, 7]);
+    /// assert_eq!(set.rank(&3), 1);
+    /// assert_eq!(set.rank(&4), 3);
+    /// assert_eq!(set.rank(&8), 4);
+    /// ```
+    pub fn rank(&self, v: &T) -> usize {
+        self.count_while(|x| x < v)
+    }
+
+    // Counts the elements in the prefix satisfying `left`
+    fn count_while<F: Fn(&T) -> bool>(&self, left: F) -> usize {
+        let (mut t, mut ans) = (&self.root, 0);
+        while let Some(node) = t {
+            if left(&node.val) {
+                ans += size(&node.l) + 1;
+                t = &node.r;
+            } else {
+                t = &node.l;
+            }
+        }
+        ans
+    }
+
+    // Finds the first element not satisfying `left`
+    fn first_not<F: Fn(&T) -> bool>(&self, left: F) -> Option<&T> {
+        let (mut t, mut ans) = (&self.root, None);
+        while let Some(node) = t {
+            if left(&node.val) {
+                t = &node.r;
+            } else {
+                ans = Some(&node.val);
+                t = &node.l;
+            }
+        }
+        ans
+    }
+
+    // Finds the last element satisfying `left`
+    fn last_with<F: Fn(&T) -> bool>(&self, left: F) -> Option<&T> {
+        let (mut t, mut ans) = (&self.root, None);
+        while let Some(node) = t {
+            if left(&node.val) {
+                ans = Some(&node.val);
+                t = &node.r;
+            } else {
+                t = &node.l;
+            }
+        }
+        ans
+    }
+
+    /// Returns the number of occurrences of `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([1, 3, 3, 7]);
+    /// assert_eq!(set.count(&3), 2);
+    /// assert_eq!(set.count(&4), 0);
+    /// ```
+    pub fn count(&self, v: &T) -> usize {
+        self.count_while(|x| x <= v) - self.rank(v)
+    }
+
+    /// Returns [`true`] if `v` is in the multiset
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    pub fn contains(&self, v: &T) -> bool {
+        self.lower_bound(v) == Some(v)
+    }
+
+    /// Returns the number of elements in the range $[lo, hi)$
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([1, 3, 3, 7]);
+    /// assert_eq!(set.range_count(&2, &7), 2);
+    /// assert_eq!(set.range_count(&7, &2), 0);
+    /// ```
+    pub fn range_count(&self, lo: &T, hi: &T) -> usize {
+        self.rank(hi).saturating_sub(self.rank(lo))
+    }
+
+    /// Returns the `k`-th smallest element (0-indexed), or [`None`] if there are not enough elements
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([7, 3, 1, 3]);
+    /// assert_eq!(set.kth(0), Some(&1));
+    /// assert_eq!(set.kth(2), Some(&3));
+    /// assert_eq!(set.kth(4), None);
+    /// ```
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        let (mut t, mut k) = (&self.root, k);
+        while let Some(node) = t {
+            let ls = size(&node.l);
+            if k == ls {
+                return Some(&node.val);
+            }
+            if k < ls {
+                t = &node.l;
+            } else {
+                (t, k) = (&node.r, k - ls - 1);
+            }
+        }
+        None
+    }
+
+    /// Returns the smallest element, if any
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the size of the multiset.
+    pub fn first(&self) -> Option<&T> {
+        self.kth(0)
+    }
+
+    /// Returns the largest element, if any
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the size of the multiset.
+    pub fn last(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|k| self.kth(k))
+    }
+
+    /// Returns the smallest element not smaller than `v`, if any
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([1, 3, 3, 7]);
+    /// assert_eq!(set.lower_bound(&3), Some(&3));
+    /// assert_eq!(set.lower_bound(&4), Some(&7));
+    /// assert_eq!(set.lower_bound(&8), None);
+    /// ```
+    pub fn lower_bound(&self, v: &T) -> Option<&T> {
+        self.first_not(|x| x < v)
+    }
+
+    /// Returns the smallest element greater than `v`, if any
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([1, 3, 3, 7]);
+    /// assert_eq!(set.upper_bound(&3), Some(&7));
+    /// assert_eq!(set.upper_bound(&0), Some(&1));
+    /// assert_eq!(set.upper_bound(&7), None);
+    /// ```
+    pub fn upper_bound(&self, v: &T) -> Option<&T> {
+        self.first_not(|x| x <= v)
+    }
+
+    /// Returns the largest element smaller than `v`, if any
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([1, 3, 3, 7]);
+    /// assert_eq!(set.predecessor(&3), Some(&1));
+    /// assert_eq!(set.predecessor(&1), None);
+    /// ```
+    pub fn predecessor(&self, v: &T) -> Option<&T> {
+        self.last_with(|x| x < v)
+    }
+
+    /// Returns the smallest element greater than `v`, if any.
+    /// This is the same as [`OrderedMultiset::upper_bound`].
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected comparisons where:
+    /// - $N$ is the size of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::ordered_set::OrderedMultiset;
+    ///
+    /// let set = OrderedMultiset::from_iter([1, 3, 3, 7]);
+    /// assert_eq!(set.successor(&3), Some(&7));
+    /// assert_eq!(set.successor(&7), None);
+    /// ```
+    pub fn successor(&self, v: &T) -> Option<&T> {
+        self.upper_bound(v)
+    }
+
+    /// Returns an iterator over the elements of the multiset, in increasing order
+    ///
+    /// Complexity: $\mathcal{O}(N)$ for a complete iteration where:
+    /// - $N$ is the size of the multiset.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_spine(&self.root);
+        iter
+    }
+}
+
+impl<T> Default for OrderedMultiset<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a multiset from an iterator of values
+///
+/// Complexity: $\mathcal{O}(N \log N)$ expected comparisons where:
+/// - $N$ is the number of values.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::ordered_set::OrderedMultiset;
+///
+/// let set = OrderedMultiset::from_iter([2, 3, 1]);
+/// assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+/// ```
+impl<T> FromIterator<T> for OrderedMultiset<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for v in iter {
+            set.insert(v);
+        }
+        set
+    }
+}
+
+/// In-order iterator over the elements of an [`OrderedMultiset`]
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_spine(&mut self, mut t: &'a Link<T>) {
+        while let Some(node) = t {
+            self.stack.push(node);
+            t = &node.l;
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_spine(&node.r);
+        Some(&node.val)
+    }
+}
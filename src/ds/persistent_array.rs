@@ -0,0 +1,381 @@
+use std::mem;
+
+#[derive(Clone, Debug)]
+enum Node<T, const B: usize> {
+    Inner([usize; B]),
+    Leaf(T),
+}
+
+/// Persistent array, as a path-copying tree of branching factor `B`
+///
+/// Every assignment creates a new version of the array, sharing all unchanged nodes with the old one.
+/// Versions are numbered in order of creation, starting from $0$ for the initial one.
+/// Nodes are stored in a single arena.
+///
+/// Larger values of `B` make the tree shallower, so that reads are faster,
+/// while assignments copy more memory.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::persistent_array::PersistentArray;
+///
+/// let mut arr: PersistentArray<_> = PersistentArray::from(&[1, 2, 3]);
+/// let v1 = arr.set(0, 0, 10);
+/// let v2 = arr.set(v1, 2, 30);
+/// let v3 = arr.set(v1, 1, 20);
+///
+/// assert_eq!(arr.to_vec(0), vec![1, 2, 3]);
+/// assert_eq!(arr.to_vec(v2), vec![10, 2, 30]);
+/// assert_eq!(arr.to_vec(v3), vec![10, 20, 3]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PersistentArray<T, const B: usize = 8> {
+    nodes: Vec<Node<T, B>>,
+    roots: Vec<usize>,
+    size: usize,
+    depth: u32,
+}
+
+impl<T, const B: usize> PersistentArray<T, B>
+where
+    T: Clone,
+{
+    fn depth(size: usize) -> u32 {
+        debug_assert!(B >= 2);
+        let (mut depth, mut cap) = (0, 1);
+        while cap < size {
+            cap *= B;
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Builds a persistent array of given `size`, filled with `val`
+    ///
+    /// Complexity: $\mathcal{O}(B \log_B N)$ where:
+    /// - $N$ is the size of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_array::PersistentArray;
+    ///
+    /// let arr: PersistentArray<_> = PersistentArray::new(1_000_000_000, 0);
+    /// assert_eq!(*arr.get(0, 123_456_789), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `B` is smaller than $2$.
+    pub fn new(size: usize, val: T) -> Self {
+        let depth = Self::depth(size);
+        // All the nodes of a level are identical, so a single one is stored
+        let mut nodes = vec![Node::Leaf(val)];
+        for _ in 0..depth {
+            nodes.push(Node::Inner([nodes.len() - 1; B]));
+        }
+
+        PersistentArray {
+            roots: vec![nodes.len() - 1],
+            nodes,
+            size,
+            depth,
+        }
+    }
+
+    /// Builds a persistent array from a slice of `T`
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the size of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_array::PersistentArray;
+    ///
+    /// let arr: PersistentArray<_, 2> = PersistentArray::from(&[1, 2, 3, 4]);
+    /// assert_eq!(*arr.get(0, 3), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `B` is smaller than $2$.
+    pub fn from(array: &[T]) -> Self {
+        let depth = Self::depth(array.len());
+        let mut arr = PersistentArray {
+            nodes: Vec::with_capacity(2 * array.len()),
+            roots: vec![],
+            size: array.len(),
+            depth,
+        };
+        if !array.is_empty() {
+            let root = arr.build(array, depth);
+            arr.roots.push(root);
+        } else {
+            arr.roots.push(usize::MAX);
+        }
+        arr
+    }
+
+    fn build(&mut self, array: &[T], depth: u32) -> usize {
+        let node = if depth == 0 {
+            Node::Leaf(array[0].clone())
+        } else {
+            let span = B.pow(depth - 1);
+            let mut children = [usize::MAX; B];
+            for (child, chunk) in children.iter_mut().zip(array.chunks(span)) {
+                *child = self.build(chunk, depth - 1);
+            }
+            Node::Inner(children)
+        };
+
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Returns the size of the array
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns [`true`] if the array is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the number of versions of the array
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn versions(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns the element at `pos` in a given `version`
+    ///
+    /// Complexity: $\mathcal{O}(\log_B N)$ where:
+    /// - $N$ is the size of the array.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::persistent_array::PersistentArray;
+    ///
+    /// let arr: PersistentArray<_> = PersistentArray::from(&[1, 2, 3, 4]);
+    /// arr.get(0, 4);
+    /// ```
+    ///
+    /// If `version` does not exist.
+    pub fn get(&self, version: usize, pos: usize) -> &T {
+        debug_assert!(pos < self.size);
+
+        let mut node = self.roots[version];
+        for level in (0..self.depth).rev() {
+            let Node::Inner(children) = &self.nodes[node] else {
+                unreachable!()
+            };
+            node = children[pos / B.pow(level) % B];
+        }
+
+        match &self.nodes[node] {
+            Node::Leaf(val) => val,
+            Node::Inner(_) => unreachable!(),
+        }
+    }
+
+    /// Creates a new version by setting the element at `pos` of a given `version` to `val`.
+    /// Returns the index of the new version.
+    ///
+    /// Complexity: $\mathcal{O}(B \log_B N)$ where:
+    /// - $N$ is the size of the array.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// If `version` does not exist.
+    pub fn set(&mut self, version: usize, pos: usize, val: T) -> usize {
+        debug_assert!(pos < self.size);
+
+        let mut path = Vec::with_capacity(self.depth as usize);
+        let mut node = self.roots[version];
+        for level in (0..self.depth).rev() {
+            let Node::Inner(children) = &self.nodes[node] else {
+                unreachable!()
+            };
+            let digit = pos / B.pow(level) % B;
+            path.push((*children, digit));
+            node = children[digit];
+        }
+
+        self.nodes.push(Node::Leaf(val));
+        for (mut children, digit) in path.into_iter().rev() {
+            children[digit] = self.nodes.len() - 1;
+            self.nodes.push(Node::Inner(children));
+        }
+
+        self.roots.push(self.nodes.len() - 1);
+        self.roots.len() - 1
+    }
+
+    /// Returns the elements of a given `version`
+    ///
+    /// Complexity: $\mathcal{O}(N \log_B N)$ where:
+    /// - $N$ is the size of the array.
+    ///
+    /// # Panics
+    ///
+    /// If `version` does not exist.
+    pub fn to_vec(&self, version: usize) -> Vec<T> {
+        (0..self.size)
+            .map(|pos| self.get(version, pos).clone())
+            .collect()
+    }
+}
+
+/// Persistent disjoint set union, with union by size on top of [`PersistentArray`]
+///
+/// Every merge creates a new version, and versions are numbered in order of creation,
+/// starting from $0$ for the initial one.
+///
+/// # Example
+///
+/// ```
+/// use cp_library::ds::persistent_array::PersistentDsu;
+///
+/// let mut dsu = PersistentDsu::new(5);
+/// let v1 = dsu.unite(0, 0, 1);
+/// let v2 = dsu.unite(v1, 1, 2);
+/// let v3 = dsu.unite(v1, 3, 4);
+///
+/// assert!(dsu.same(v2, 0, 2));
+/// assert!(!dsu.same(v3, 0, 2));
+/// assert!(dsu.same(v3, 3, 4));
+/// assert_eq!(dsu.components(v3), 3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PersistentDsu {
+    // Parent of each element, or the size of its set for representatives, stored as !size
+    parent: PersistentArray<usize>,
+    // Version of the array and number of components of each version
+    versions: Vec<(usize, usize)>,
+}
+
+impl PersistentDsu {
+    /// Builds a persistent disjoint set union of `size` singletons
+    ///
+    /// Complexity: $\mathcal{O}(B \log_B N)$ where:
+    /// - $N$ is the number of elements;
+    /// - $B$ is the branching factor of the underlying [`PersistentArray`].
+    pub fn new(size: usize) -> Self {
+        PersistentDsu {
+            parent: PersistentArray::new(size, !1),
+            versions: vec![(0, size)],
+        }
+    }
+
+    /// Returns the number of versions
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn versions(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Finds the representative of the set containing `v` in a given `version`
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log_B N)$ where:
+    /// - $N$ is the number of elements;
+    /// - $B$ is the branching factor of the underlying [`PersistentArray`].
+    ///
+    /// # Panics
+    ///
+    /// If `version` does not exist.
+    pub fn find(&self, version: usize, v: usize) -> usize {
+        let array_version = self.versions[version].0;
+        let mut v = v;
+        loop {
+            let p = *self.parent.get(array_version, v);
+            if p > self.parent.len() {
+                return v;
+            }
+            v = p;
+        }
+    }
+
+    /// Creates a new version by merging the sets containing `u` and `v` in a given `version`.
+    /// Returns the index of the new version, which is a copy of `version` if they were already in the same set.
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log_B N + B \log_B N)$ where:
+    /// - $N$ is the number of elements;
+    /// - $B$ is the branching factor of the underlying [`PersistentArray`].
+    ///
+    /// # Panics
+    ///
+    /// If `version` does not exist.
+    pub fn unite(&mut self, version: usize, u: usize, v: usize) -> usize {
+        let (array_version, components) = self.versions[version];
+        let (mut u, mut v) = (self.find(version, u), self.find(version, v));
+        if u == v {
+            self.versions.push((array_version, components));
+            return self.versions.len() - 1;
+        }
+
+        let (mut su, mut sv) = (
+            !*self.parent.get(array_version, u),
+            !*self.parent.get(array_version, v),
+        );
+        if su < sv {
+            mem::swap(&mut u, &mut v);
+            mem::swap(&mut su, &mut sv);
+        }
+
+        let array_version = self.parent.set(array_version, v, u);
+        let array_version = self.parent.set(array_version, u, !(su + sv));
+        self.versions.push((array_version, components - 1));
+        self.versions.len() - 1
+    }
+
+    /// Checks whether `u` and `v` are in the same set in a given `version`
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log_B N)$ where:
+    /// - $N$ is the number of elements;
+    /// - $B$ is the branching factor of the underlying [`PersistentArray`].
+    ///
+    /// # Panics
+    ///
+    /// If `version` does not exist.
+    pub fn same(&self, version: usize, u: usize, v: usize) -> bool {
+        self.find(version, u) == self.find(version, v)
+    }
+
+    /// Returns the size of the set containing `v` in a given `version`
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log_B N)$ where:
+    /// - $N$ is the number of elements;
+    /// - $B$ is the branching factor of the underlying [`PersistentArray`].
+    ///
+    /// # Panics
+    ///
+    /// If `version` does not exist.
+    pub fn size(&self, version: usize, v: usize) -> usize {
+        let root = self.find(version, v);
+        !*self.parent.get(self.versions[version].0, root)
+    }
+
+    /// Returns the number of disjoint sets in a given `version`
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Panics
+    ///
+    /// If `version` does not exist.
+    pub fn components(&self, version: usize) -> usize {
+        self.versions[version].1
+    }
+}
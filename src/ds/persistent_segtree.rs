@@ -0,0 +1,322 @@
+use crate::math::algebra::{Abelian, Monoid};
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    val: T,
+    l: usize,
+    r: usize,
+}
+
+/// Persistent segment tree over a [monoid](https://en.wikipedia.org/wiki/Monoid) `T`
+///
+/// Every update creates a new version of the tree, sharing all unchanged nodes with the old one.
+/// Versions are numbered in order of creation, starting from $0$ for the initial one.
+/// Nodes are stored in a single arena.
+///
+/// `T` must be [`Clone`] and [`Monoid`]
+#[derive(Clone, Debug)]
+pub struct PersistentSegTree<T> {
+    nodes: Vec<Node<T>>,
+    roots: Vec<usize>,
+    size: usize,
+}
+
+impl<T> PersistentSegTree<T>
+where
+    T: Clone + Monoid,
+{
+    /// Builds a persistent segment tree of given `size`, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let x: PersistentSegTree<i32> = PersistentSegTree::new(10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        // Node 0 represents a range of identity elements of any length, so it is its own child
+        PersistentSegTree {
+            nodes: vec![Node {
+                val: T::ID,
+                l: 0,
+                r: 0,
+            }],
+            roots: vec![0],
+            size,
+        }
+    }
+
+    /// Builds a persistent segment tree from a slice of `T`.
+    ///
+    /// Complexity: $\mathcal{O}(N)$ monoid operations where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// ```
+    pub fn from(array: &[T]) -> Self {
+        let mut tree = Self::new(array.len());
+        tree.nodes.reserve(2 * array.len());
+        if !array.is_empty() {
+            let root = tree.build(array);
+            tree.roots[0] = root;
+        }
+        tree
+    }
+
+    fn build(&mut self, array: &[T]) -> usize {
+        if array.len() == 1 {
+            return self.push(array[0].clone(), 0, 0);
+        }
+
+        let mid = array.len() / 2;
+        let l = self.build(&array[..mid]);
+        let r = self.build(&array[mid..]);
+        let val = self.nodes[l].val.clone().op(self.nodes[r].val.clone());
+        self.push(val, l, r)
+    }
+
+    fn push(&mut self, val: T, l: usize, r: usize) -> usize {
+        self.nodes.push(Node { val, l, r });
+        self.nodes.len() - 1
+    }
+
+    /// Returns the number of versions of the tree
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let mut x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.versions(), 1);
+    /// x.update(0, 2, &4);
+    /// assert_eq!(x.versions(), 2);
+    /// ```
+    pub fn versions(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Perform a range query on the range $[l, r)$ of a given `version`.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.query(0, 1, 3), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range:
+    /// ```should_panic
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// let y = x.query(0, 3, 2);
+    /// ```
+    ///
+    /// If `version` does not exist:
+    /// ```should_panic
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// let y = x.query(1, 1, 3);
+    /// ```
+    pub fn query(&self, version: usize, l: usize, r: usize) -> T {
+        debug_assert!(l <= r && r <= self.size);
+        self.query_rec(self.roots[version], 0, self.size, l, r)
+    }
+
+    fn query_rec(&self, node: usize, nl: usize, nr: usize, l: usize, r: usize) -> T {
+        if r <= nl || nr <= l {
+            return T::ID;
+        }
+        if l <= nl && nr <= r {
+            return self.nodes[node].val.clone();
+        }
+
+        let mid = (nl + nr) / 2;
+        let Node { l: cl, r: cr, .. } = self.nodes[node];
+        self.query_rec(cl, nl, mid, l, r)
+            .op(self.query_rec(cr, mid, nr, l, r))
+    }
+
+    /// Returns the element at `pos` in a given `version`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.get(0, 2), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// x.get(0, 4);
+    /// ```
+    pub fn get(&self, version: usize, pos: usize) -> T {
+        debug_assert!(pos < self.size);
+
+        let (mut node, mut nl, mut nr) = (self.roots[version], 0, self.size);
+        while nr - nl > 1 {
+            let mid = (nl + nr) / 2;
+            if pos < mid {
+                (node, nr) = (self.nodes[node].l, mid);
+            } else {
+                (node, nl) = (self.nodes[node].r, mid);
+            }
+        }
+
+        self.nodes[node].val.clone()
+    }
+
+    /// Creates a new version by setting the element at `pos` of a given `version` to `val`.
+    /// Returns the index of the new version.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ monoid operations and new nodes where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let mut x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// let v1 = x.update(0, 2, &4);
+    /// let v2 = x.update(0, 1, &0);
+    ///
+    /// assert_eq!(x.query(0, 1, 3), 5);
+    /// assert_eq!(x.query(v1, 1, 3), 6);
+    /// assert_eq!(x.query(v2, 1, 3), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let mut x = PersistentSegTree::from(&[1, 2, 3, 4]);
+    /// x.update(0, 4, &4);
+    /// ```
+    pub fn update(&mut self, version: usize, pos: usize, val: &T) -> usize {
+        debug_assert!(pos < self.size);
+
+        let root = self.update_rec(self.roots[version], 0, self.size, pos, val);
+        self.roots.push(root);
+        self.roots.len() - 1
+    }
+
+    fn update_rec(&mut self, node: usize, nl: usize, nr: usize, pos: usize, val: &T) -> usize {
+        if nr - nl == 1 {
+            return self.push(val.clone(), 0, 0);
+        }
+
+        let mid = (nl + nr) / 2;
+        let Node { l, r, .. } = self.nodes[node];
+        let (l, r) = if pos < mid {
+            (self.update_rec(l, nl, mid, pos, val), r)
+        } else {
+            (l, self.update_rec(r, mid, nr, pos, val))
+        };
+
+        let val = self.nodes[l].val.clone().op(self.nodes[r].val.clone());
+        self.push(val, l, r)
+    }
+}
+
+impl<T> PersistentSegTree<T>
+where
+    T: Clone + Abelian + PartialOrd,
+{
+    /// Finds the first position $p$ such that the sum of the range $[0, p]$
+    /// is greater than `k` in the difference between versions `hi` and `lo`,
+    /// or the size of the tree if none exists.
+    ///
+    /// All elements of the difference must be non-negative.
+    ///
+    /// If version $i$ counts the occurrences of each (compressed) value among the first $i$
+    /// elements of an array $a$, this finds the `k`-th smallest element (0-indexed)
+    /// of the subarray $a[lo..hi]$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ group operations where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::persistent_segtree::PersistentSegTree;
+    ///
+    /// let arr = [3, 1, 4, 1, 5, 9, 2, 6];
+    ///
+    /// let mut counts: PersistentSegTree<i32> = PersistentSegTree::new(10);
+    /// for (i, &x) in arr.iter().enumerate() {
+    ///     let c = counts.get(i, x);
+    ///     counts.update(i, x, &(c + 1));
+    /// }
+    ///
+    /// assert_eq!(counts.kth(2, 6, 0), 1);
+    /// assert_eq!(counts.kth(2, 6, 1), 4);
+    /// assert_eq!(counts.kth(2, 6, 3), 9);
+    /// assert_eq!(counts.kth(2, 6, 4), 10);
+    /// assert_eq!(counts.kth(0, 8, 2), 2);
+    /// ```
+    pub fn kth(&self, lo: usize, hi: usize, k: T) -> usize {
+        let (mut a, mut b) = (self.roots[hi], self.roots[lo]);
+        let (mut nl, mut nr) = (0, self.size);
+        let mut k = k;
+
+        let total = self.nodes[a]
+            .val
+            .clone()
+            .op(self.nodes[b].val.clone().inv());
+        if self.size == 0 || total <= k {
+            return self.size;
+        }
+
+        while nr - nl > 1 {
+            let mid = (nl + nr) / 2;
+            let (al, bl) = (self.nodes[a].l, self.nodes[b].l);
+            let left = self.nodes[al]
+                .val
+                .clone()
+                .op(self.nodes[bl].val.clone().inv());
+
+            if k < left {
+                (a, b, nr) = (al, bl, mid);
+            } else {
+                k = k.op(left.inv());
+                (a, b, nl) = (self.nodes[a].r, self.nodes[b].r, mid);
+            }
+        }
+
+        nl
+    }
+}
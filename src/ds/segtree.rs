@@ -193,3 +193,216 @@ where
         &self.arr[pos + self.size]
     }
 }
+
+/// Two-dimensional segment tree over a commutative [monoid](https://en.wikipedia.org/wiki/Monoid) `T`
+///
+/// Supports point updates and rectangle queries on a dense grid.
+/// Since the cells of a rectangle are not folded in row-major order, `T` should be commutative.
+///
+/// `T` must be [`Clone`] and [`Monoid`]
+#[derive(Clone, Debug)]
+pub struct SegTree2D<T> {
+    arr: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> SegTree2D<T>
+where
+    T: Clone + Monoid,
+{
+    /// Builds a segment tree over a grid with `rows` rows and `cols` columns, filled with identity elements
+    ///
+    /// Complexity: $\mathcal{O}(NM)$ where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree::SegTree2D;
+    ///
+    /// let x: SegTree2D<i32> = SegTree2D::new(3, 4);
+    /// ```
+    pub fn new(rows: usize, cols: usize) -> Self {
+        SegTree2D {
+            arr: vec![<T as Monoid>::ID; 4 * rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Builds a segment tree from a grid of `T`, given as a slice of rows of equal length.
+    ///
+    /// Complexity: $\mathcal{O}(NM)$ monoid operations where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree::SegTree2D;
+    ///
+    /// let x = SegTree2D::from(&[vec![1, 2, 3], vec![4, 5, 6]]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if the rows do not have the same length.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::segtree::SegTree2D;
+    ///
+    /// let x = SegTree2D::from(&[vec![1, 2, 3], vec![4, 5]]);
+    /// ```
+    pub fn from(grid: &[Vec<T>]) -> Self {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, Vec::len);
+        debug_assert!(grid.iter().all(|row| row.len() == cols));
+
+        let mut tree = Self::new(rows, cols);
+        let width = 2 * cols;
+
+        for (i, row) in grid.iter().enumerate() {
+            let base = (i + rows) * width;
+            for (j, val) in row.iter().enumerate() {
+                tree.arr[base + j + cols] = val.clone();
+            }
+            for j in (1..cols).rev() {
+                tree.arr[base + j] = tree.arr[base + 2 * j]
+                    .clone()
+                    .op(tree.arr[base + 2 * j + 1].clone());
+            }
+        }
+
+        for i in (1..rows).rev() {
+            for j in 1..width {
+                tree.arr[i * width + j] = tree.arr[2 * i * width + j]
+                    .clone()
+                    .op(tree.arr[(2 * i + 1) * width + j].clone());
+            }
+        }
+
+        tree
+    }
+
+    /// Perform a query on the rectangle $[x_l, x_r) \times [y_l, y_r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log M)$ monoid operations where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree::SegTree2D;
+    ///
+    /// let x = SegTree2D::from(&[vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
+    /// assert_eq!(x.query(1, 3, 0, 2), 24);
+    /// assert_eq!(x.query(0, 2, 2, 2), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if the ranges are not valid:
+    /// ```should_panic
+    /// use cp_library::ds::segtree::SegTree2D;
+    ///
+    /// let x = SegTree2D::from(&[vec![1, 2, 3], vec![4, 5, 6]]);
+    /// let y = x.query(0, 3, 0, 1);
+    /// ```
+    pub fn query(&self, xl: usize, xr: usize, yl: usize, yr: usize) -> T {
+        debug_assert!(xl <= xr && xr <= self.rows);
+        debug_assert!(yl <= yr && yr <= self.cols);
+
+        let mut ans: T = <T as Monoid>::ID;
+
+        let (mut l, mut r) = (xl + self.rows, xr + self.rows);
+        while l < r {
+            if (l & 1) == 1 {
+                ans = ans.op(self.query_row(l, yl, yr));
+                l += 1;
+            }
+            if (r & 1) == 1 {
+                r -= 1;
+                ans = ans.op(self.query_row(r, yl, yr));
+            }
+
+            (l, r) = (l >> 1, r >> 1);
+        }
+
+        ans
+    }
+
+    // Queries the columns [l, r) of the node `row` of the outer tree
+    fn query_row(&self, row: usize, l: usize, r: usize) -> T {
+        let base = row * 2 * self.cols;
+        let mut ans_l: T = <T as Monoid>::ID;
+        let mut ans_r: T = <T as Monoid>::ID;
+
+        let (mut l, mut r) = (l + self.cols, r + self.cols);
+        while l < r {
+            if (l & 1) == 1 {
+                ans_l = ans_l.op(self.arr[base + l].clone());
+                l += 1;
+            }
+            if (r & 1) == 1 {
+                r -= 1;
+                ans_r = self.arr[base + r].clone().op(ans_r);
+            }
+
+            (l, r) = (l >> 1, r >> 1);
+        }
+
+        ans_l.op(ans_r)
+    }
+
+    /// Updates the element in cell $(x, y)$ with value `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N \log M)$ monoid operations where:
+    /// - $N$ and $M$ are the dimensions of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree::SegTree2D;
+    ///
+    /// let mut x = SegTree2D::from(&[vec![1, 2, 3], vec![4, 5, 6]]);
+    /// x.update(1, 1, &10);
+    /// assert_eq!(x.query(0, 2, 1, 3), 21);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug mode, if $(x, y)$ is not a valid cell.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::segtree::SegTree2D;
+    ///
+    /// let mut x = SegTree2D::from(&[vec![1, 2, 3], vec![4, 5, 6]]);
+    /// x.update(2, 0, &4);
+    /// ```
+    pub fn update(&mut self, x: usize, y: usize, val: &T) {
+        debug_assert!(x < self.rows && y < self.cols);
+
+        let width = 2 * self.cols;
+        let mut i = x + self.rows;
+
+        let mut j = y + self.cols;
+        self.arr[i * width + j] = val.clone();
+        while j > 1 {
+            j >>= 1;
+            self.arr[i * width + j] = self.arr[i * width + 2 * j]
+                .clone()
+                .op(self.arr[i * width + 2 * j + 1].clone());
+        }
+
+        while i > 1 {
+            i >>= 1;
+            let mut j = y + self.cols;
+            while j > 0 {
+                self.arr[i * width + j] = self.arr[2 * i * width + j]
+                    .clone()
+                    .op(self.arr[(2 * i + 1) * width + j].clone());
+                j >>= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use std::cmp;
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    max1: i64,
+    max2: i64,
+    max_cnt: i64,
+    min1: i64,
+    min2: i64,
+    min_cnt: i64,
+    sum: i64,
+    len: i64,
+    add: i64,
+}
+
+impl Node {
+    const EMPTY: Node = Node {
+        max1: i64::MIN,
+        max2: i64::MIN,
+        max_cnt: 0,
+        min1: i64::MAX,
+        min2: i64::MAX,
+        min_cnt: 0,
+        sum: 0,
+        len: 0,
+        add: 0,
+    };
+
+    fn leaf(val: i64) -> Self {
+        Node {
+            max1: val,
+            max2: i64::MIN,
+            max_cnt: 1,
+            min1: val,
+            min2: i64::MAX,
+            min_cnt: 1,
+            sum: val,
+            len: 1,
+            add: 0,
+        }
+    }
+
+    fn merge(l: &Node, r: &Node) -> Self {
+        let (max1, max2, max_cnt) = match l.max1.cmp(&r.max1) {
+            cmp::Ordering::Equal => (l.max1, cmp::max(l.max2, r.max2), l.max_cnt + r.max_cnt),
+            cmp::Ordering::Greater => (l.max1, cmp::max(l.max2, r.max1), l.max_cnt),
+            cmp::Ordering::Less => (r.max1, cmp::max(l.max1, r.max2), r.max_cnt),
+        };
+        let (min1, min2, min_cnt) = match l.min1.cmp(&r.min1) {
+            cmp::Ordering::Equal => (l.min1, cmp::min(l.min2, r.min2), l.min_cnt + r.min_cnt),
+            cmp::Ordering::Less => (l.min1, cmp::min(l.min2, r.min1), l.min_cnt),
+            cmp::Ordering::Greater => (r.min1, cmp::min(l.min1, r.min2), r.min_cnt),
+        };
+
+        Node {
+            max1,
+            max2,
+            max_cnt,
+            min1,
+            min2,
+            min_cnt,
+            sum: l.sum + r.sum,
+            len: l.len + r.len,
+            add: 0,
+        }
+    }
+
+    fn apply_add(&mut self, x: i64) {
+        self.max1 += x;
+        if self.max2 != i64::MIN {
+            self.max2 += x;
+        }
+        self.min1 += x;
+        if self.min2 != i64::MAX {
+            self.min2 += x;
+        }
+        self.sum += x * self.len;
+        self.add += x;
+    }
+
+    // Lowers the maximum to x, requires max2 < x
+    fn apply_chmin(&mut self, x: i64) {
+        self.sum += (x - self.max1) * self.max_cnt;
+        if self.max1 == self.min1 {
+            self.min1 = x;
+        } else if self.max1 == self.min2 {
+            self.min2 = x;
+        }
+        self.max1 = x;
+    }
+
+    // Raises the minimum to x, requires min2 > x
+    fn apply_chmax(&mut self, x: i64) {
+        self.sum += (x - self.min1) * self.min_cnt;
+        if self.min1 == self.max1 {
+            self.max1 = x;
+        } else if self.min1 == self.max2 {
+            self.max2 = x;
+        }
+        self.min1 = x;
+    }
+}
+
+/// Segment tree beats (Ji driver segment tree) over `i64`
+///
+/// Supports range chmin, range chmax and range add updates,
+/// together with range sum, maximum and minimum queries.
+#[derive(Clone, Debug)]
+pub struct SegTreeBeats {
+    nodes: Vec<Node>,
+    size: usize,
+}
+
+impl SegTreeBeats {
+    /// Builds a segment tree beats of given `size`, filled with zeros
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let x = SegTreeBeats::new(10);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        Self::from(&vec![0; size])
+    }
+
+    /// Builds a segment tree beats from a slice of `i64`.
+    ///
+    /// Complexity: $\mathcal{O}(N)$ where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let x = SegTreeBeats::from(&[1, 2, 3, 4]);
+    /// ```
+    pub fn from(array: &[i64]) -> Self {
+        let size = array.len();
+        let mut tree = SegTreeBeats {
+            nodes: vec![Node::EMPTY; 4 * cmp::max(size, 1)],
+            size,
+        };
+        if size > 0 {
+            tree.build(1, 0, size, array);
+        }
+        tree
+    }
+
+    fn build(&mut self, k: usize, nl: usize, nr: usize, array: &[i64]) {
+        if nr - nl == 1 {
+            self.nodes[k] = Node::leaf(array[nl]);
+            return;
+        }
+
+        let mid = (nl + nr) / 2;
+        self.build(2 * k, nl, mid, array);
+        self.build(2 * k + 1, mid, nr, array);
+        self.pull(k);
+    }
+
+    fn pull(&mut self, k: usize) {
+        self.nodes[k] = Node::merge(&self.nodes[2 * k], &self.nodes[2 * k + 1]);
+    }
+
+    fn push(&mut self, k: usize) {
+        let Node {
+            add, max1, min1, ..
+        } = self.nodes[k];
+
+        for c in [2 * k, 2 * k + 1] {
+            let child = &mut self.nodes[c];
+            if add != 0 {
+                child.apply_add(add);
+            }
+            if child.max1 > max1 {
+                child.apply_chmin(max1);
+            }
+            if child.min1 < min1 {
+                child.apply_chmax(min1);
+            }
+        }
+
+        self.nodes[k].add = 0;
+    }
+
+    /// Replaces every element $a_i$ in the range $[l, r)$ with $\min(a_i, x)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ amortized where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let mut x = SegTreeBeats::from(&[1, 5, 3, 7]);
+    /// x.chmin(0, 3, 2);
+    /// assert_eq!(x.sum(0, 4), 12);
+    /// assert_eq!(x.max(0, 3), 2);
+    ///
+    /// let mut y = SegTreeBeats::from(&[0]);
+    /// y.chmin(0, 1, i64::MIN);
+    /// assert_eq!(y.min(0, 1), i64::MIN);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range:
+    /// ```should_panic
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let mut x = SegTreeBeats::from(&[1, 2, 3, 4]);
+    /// x.chmin(3, 2, 0);
+    /// ```
+    pub fn chmin(&mut self, l: usize, r: usize, x: i64) {
+        debug_assert!(l <= r && r <= self.size);
+        if l < r {
+            self.chmin_rec(1, 0, self.size, l, r, x);
+        }
+    }
+
+    fn chmin_rec(&mut self, k: usize, nl: usize, nr: usize, l: usize, r: usize, x: i64) {
+        if r <= nl || nr <= l || self.nodes[k].max1 <= x {
+            return;
+        }
+        if l <= nl && nr <= r && (nr - nl == 1 || self.nodes[k].max2 < x) {
+            self.nodes[k].apply_chmin(x);
+            return;
+        }
+
+        let mid = (nl + nr) / 2;
+        self.push(k);
+        self.chmin_rec(2 * k, nl, mid, l, r, x);
+        self.chmin_rec(2 * k + 1, mid, nr, l, r, x);
+        self.pull(k);
+    }
+
+    /// Replaces every element $a_i$ in the range $[l, r)$ with $\max(a_i, x)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log^2 N)$ amortized where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let mut x = SegTreeBeats::from(&[1, 5, 3, 7]);
+    /// x.chmax(1, 4, 6);
+    /// assert_eq!(x.sum(0, 4), 20);
+    /// assert_eq!(x.min(1, 4), 6);
+    ///
+    /// let mut y = SegTreeBeats::from(&[0, 0, -4]);
+    /// y.chmax(1, 2, i64::MAX);
+    /// assert_eq!(y.max(0, 3), i64::MAX);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn chmax(&mut self, l: usize, r: usize, x: i64) {
+        debug_assert!(l <= r && r <= self.size);
+        if l < r {
+            self.chmax_rec(1, 0, self.size, l, r, x);
+        }
+    }
+
+    fn chmax_rec(&mut self, k: usize, nl: usize, nr: usize, l: usize, r: usize, x: i64) {
+        if r <= nl || nr <= l || self.nodes[k].min1 >= x {
+            return;
+        }
+        if l <= nl && nr <= r && (nr - nl == 1 || self.nodes[k].min2 > x) {
+            self.nodes[k].apply_chmax(x);
+            return;
+        }
+
+        let mid = (nl + nr) / 2;
+        self.push(k);
+        self.chmax_rec(2 * k, nl, mid, l, r, x);
+        self.chmax_rec(2 * k + 1, mid, nr, l, r, x);
+        self.pull(k);
+    }
+
+    /// Adds `x` to every element in the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let mut x = SegTreeBeats::from(&[1, 5, 3, 7]);
+    /// x.add(1, 3, -4);
+    /// assert_eq!(x.sum(0, 4), 8);
+    /// assert_eq!(x.min(0, 4), -1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn add(&mut self, l: usize, r: usize, x: i64) {
+        debug_assert!(l <= r && r <= self.size);
+        if l < r {
+            self.add_rec(1, 0, self.size, l, r, x);
+        }
+    }
+
+    fn add_rec(&mut self, k: usize, nl: usize, nr: usize, l: usize, r: usize, x: i64) {
+        if r <= nl || nr <= l {
+            return;
+        }
+        if l <= nl && nr <= r {
+            self.nodes[k].apply_add(x);
+            return;
+        }
+
+        let mid = (nl + nr) / 2;
+        self.push(k);
+        self.add_rec(2 * k, nl, mid, l, r, x);
+        self.add_rec(2 * k + 1, mid, nr, l, r, x);
+        self.pull(k);
+    }
+
+    fn query_rec(&mut self, k: usize, nl: usize, nr: usize, l: usize, r: usize) -> Node {
+        if r <= nl || nr <= l {
+            return Node::EMPTY;
+        }
+        if l <= nl && nr <= r {
+            return self.nodes[k];
+        }
+
+        let mid = (nl + nr) / 2;
+        self.push(k);
+        Node::merge(
+            &self.query_rec(2 * k, nl, mid, l, r),
+            &self.query_rec(2 * k + 1, mid, nr, l, r),
+        )
+    }
+
+    /// Returns the sum of the elements in the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let mut x = SegTreeBeats::from(&[1, 5, 3, 7]);
+    /// assert_eq!(x.sum(1, 3), 8);
+    /// assert_eq!(x.sum(2, 2), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn sum(&mut self, l: usize, r: usize) -> i64 {
+        debug_assert!(l <= r && r <= self.size);
+        if l < r {
+            self.query_rec(1, 0, self.size, l, r).sum
+        } else {
+            0
+        }
+    }
+
+    /// Returns the maximum of the elements in the range $[l, r)$,
+    /// or [`i64::MIN`] if the range is empty.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let mut x = SegTreeBeats::from(&[1, 5, 3, 7]);
+    /// assert_eq!(x.max(0, 3), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn max(&mut self, l: usize, r: usize) -> i64 {
+        debug_assert!(l <= r && r <= self.size);
+        if l < r {
+            self.query_rec(1, 0, self.size, l, r).max1
+        } else {
+            i64::MIN
+        }
+    }
+
+    /// Returns the minimum of the elements in the range $[l, r)$,
+    /// or [`i64::MAX`] if the range is empty.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ where:
+    /// - $N$ is the size of the segment tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::segtree_beats::SegTreeBeats;
+    ///
+    /// let mut x = SegTreeBeats::from(&[1, 5, 3, 7]);
+    /// assert_eq!(x.min(1, 4), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn min(&mut self, l: usize, r: usize) -> i64 {
+        debug_assert!(l <= r && r <= self.size);
+        if l < r {
+            self.query_rec(1, 0, self.size, l, r).min1
+        } else {
+            i64::MAX
+        }
+    }
+}
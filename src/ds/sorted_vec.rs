@@ -1,12 +1,98 @@
 use std::convert::From;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::binsearch::first_true;
 
 /// A sorted vector over a total order (`Ord`) `T`
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct SortedVec<T> {
     arr: Vec<T>,
+    // Set on mutable dereferencing, so that sortedness is checked on the next read
+    #[cfg(debug_assertions)]
+    dirty: AtomicBool,
+}
+
+impl<T> SortedVec<T> {
+    fn from_vec_unchecked(arr: Vec<T>) -> Self {
+        Self {
+            arr,
+            #[cfg(debug_assertions)]
+            dirty: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<T> SortedVec<T>
+where
+    T: Ord,
+{
+    fn validate(&self) {
+        #[cfg(debug_assertions)]
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            assert!(
+                self.arr.windows(2).all(|w| w[0] <= w[1]),
+                "SortedVec was left unsorted after a mutable access"
+            );
+        }
+    }
+
+    // Walks both vectors in order, pairing equal elements,
+    // and keeps the elements only in `self`, only in `other` or in both as requested
+    fn set_op(&self, other: &Self, only_self: bool, only_other: bool, both: bool) -> Self
+    where
+        T: Clone,
+    {
+        self.validate();
+        other.validate();
+
+        let (a, b) = (&self.arr, &other.arr);
+        let (mut i, mut j) = (0, 0);
+        let mut arr = vec![];
+
+        while i < a.len() && j < b.len() {
+            if a[i] < b[j] {
+                if only_self {
+                    arr.push(a[i].clone());
+                }
+                i += 1;
+            } else if b[j] < a[i] {
+                if only_other {
+                    arr.push(b[j].clone());
+                }
+                j += 1;
+            } else {
+                if both {
+                    arr.push(a[i].clone());
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+
+        if only_self {
+            arr.extend_from_slice(&a[i..]);
+        }
+        if only_other {
+            arr.extend_from_slice(&b[j..]);
+        }
+
+        Self::from_vec_unchecked(arr)
+    }
+}
+
+impl<T> Clone for SortedVec<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            arr: self.arr.clone(),
+            #[cfg(debug_assertions)]
+            dirty: AtomicBool::new(self.dirty.load(Ordering::Relaxed)),
+        }
+    }
 }
 
 impl<T> SortedVec<T>
@@ -37,9 +123,7 @@ where
     /// ```
     pub fn from_sorted_slice(slice: &[T]) -> Self {
         debug_assert!(slice.windows(2).all(|w| w[0] <= w[1]));
-        Self {
-            arr: slice.to_vec(),
-        }
+        Self::from_vec_unchecked(slice.to_vec())
     }
 
     /// Reduce multiple occurrences of a value to a single one
@@ -58,15 +142,8 @@ where
     /// assert_eq!(*arr, vec![1, 2, 3, 4]);
     /// ```
     pub fn make_unique(&mut self) {
-        let mut i = 0;
-        for j in 0..self.len() {
-            if j + 1 == self.len() || self[j] != self[j + 1] {
-                self[i] = self[j].clone();
-                i += 1;
-            }
-        }
-
-        self.resize_with(i, || unreachable!());
+        self.validate();
+        self.arr.dedup();
     }
 
     /// Finds the index of the first element which is not smaller than `v`,
@@ -84,8 +161,14 @@ where
     ///
     /// assert_eq!(arr.lower_bound(&4), 4);
     /// assert_eq!(arr.lower_bound(&5), 6);
+    ///
+    /// let empty = SortedVec::from(Vec::<i32>::new());
+    /// assert_eq!(empty.lower_bound(&5), 0);
     /// ```
     pub fn lower_bound(&self, v: &T) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
         first_true(0, self.len(), |idx| &self[idx] >= v)
     }
 
@@ -106,8 +189,262 @@ where
     /// assert_eq!(arr.upper_bound(&5), 6);
     /// ```
     pub fn upper_bound(&self, v: &T) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
         first_true(0, self.len(), |idx| &self[idx] > v)
     }
+
+    /// Returns the range of indices of the elements equal to `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ comparisons where
+    /// - $N$ is the length of the sorted vector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let arr = SortedVec::from(vec![1, 2, 2, 3, 4, 4]);
+    ///
+    /// assert_eq!(arr.equal_range(&2), 1..3);
+    /// assert_eq!(arr.equal_range(&5), 6..6);
+    /// ```
+    pub fn equal_range(&self, v: &T) -> Range<usize> {
+        self.lower_bound(v)..self.upper_bound(v)
+    }
+
+    /// Returns the number of elements equal to `v`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ comparisons where
+    /// - $N$ is the length of the sorted vector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let arr = SortedVec::from(vec![1, 2, 2, 3, 4, 4]);
+    ///
+    /// assert_eq!(arr.count(&4), 2);
+    /// assert_eq!(arr.count(&0), 0);
+    /// ```
+    pub fn count(&self, v: &T) -> usize {
+        self.equal_range(v).len()
+    }
+
+    /// Checks whether an element equal to `v` is present
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ comparisons where
+    /// - $N$ is the length of the sorted vector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let arr = SortedVec::from(vec![1, 2, 2, 3, 4, 4]);
+    ///
+    /// assert!(arr.contains(&3));
+    /// assert!(!arr.contains(&5));
+    /// ```
+    pub fn contains(&self, v: &T) -> bool {
+        self.binary_search(v).is_ok()
+    }
+
+    /// Inserts `v` after all the elements not greater than it, returning its position
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ comparisons and $\mathcal{O}(N)$ moves where
+    /// - $N$ is the length of the sorted vector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let mut arr = SortedVec::from(vec![1, 2, 4]);
+    ///
+    /// assert_eq!(arr.insert(3), 2);
+    /// assert_eq!(arr.insert(1), 1);
+    /// assert_eq!(*arr, vec![1, 1, 2, 3, 4]);
+    ///
+    /// let mut empty = SortedVec::from(vec![]);
+    /// assert_eq!(empty.insert(5), 0);
+    /// ```
+    pub fn insert(&mut self, v: T) -> usize {
+        let pos = self.upper_bound(&v);
+        self.arr.insert(pos, v);
+        pos
+    }
+
+    /// Removes one element equal to `v`.
+    /// Returns [`false`] if there was none.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ comparisons and $\mathcal{O}(N)$ moves where
+    /// - $N$ is the length of the sorted vector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let mut arr = SortedVec::from(vec![1, 2, 2, 3]);
+    ///
+    /// assert!(arr.remove_one(&2));
+    /// assert!(!arr.remove_one(&5));
+    /// assert_eq!(*arr, vec![1, 2, 3]);
+    /// ```
+    pub fn remove_one(&mut self, v: &T) -> bool {
+        let pos = self.lower_bound(v);
+        let found = pos < self.arr.len() && &self.arr[pos] == v;
+        if found {
+            self.arr.remove(pos);
+        }
+        found
+    }
+
+    /// Removes all the elements equal to `v`, returning how many there were.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ comparisons and $\mathcal{O}(N)$ moves where
+    /// - $N$ is the length of the sorted vector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let mut arr = SortedVec::from(vec![1, 2, 2, 3]);
+    ///
+    /// assert_eq!(arr.remove_all(&2), 2);
+    /// assert_eq!(*arr, vec![1, 3]);
+    /// ```
+    pub fn remove_all(&mut self, v: &T) -> usize {
+        let range = self.equal_range(v);
+        let count = range.len();
+        self.arr.drain(range);
+        count
+    }
+
+    /// Moves all the elements of `other` into `self`, keeping it sorted
+    ///
+    /// Complexity: $\mathcal{O}(N + M)$ comparisons and moves where
+    /// - $N$ and $M$ are the lengths of the sorted vectors
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let mut arr = SortedVec::from(vec![1, 3, 5]);
+    /// arr.merge(SortedVec::from(vec![2, 3, 6]));
+    ///
+    /// assert_eq!(*arr, vec![1, 2, 3, 3, 5, 6]);
+    /// ```
+    pub fn merge(&mut self, other: Self) {
+        self.validate();
+        other.validate();
+
+        let a = std::mem::take(&mut self.arr);
+        let mut arr = Vec::with_capacity(a.len() + other.arr.len());
+        let mut a = a.into_iter().peekable();
+        let mut b = other.arr.into_iter().peekable();
+
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            if y < x {
+                arr.push(b.next().unwrap());
+            } else {
+                arr.push(a.next().unwrap());
+            }
+        }
+        arr.extend(a);
+        arr.extend(b);
+
+        self.arr = arr;
+    }
+
+    /// Returns the union of `self` and `other`.
+    ///
+    /// As a multiset operation, each value appears as many times as in the one containing more of it.
+    ///
+    /// Complexity: $\mathcal{O}(N + M)$ comparisons and copies where
+    /// - $N$ and $M$ are the lengths of the sorted vectors
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let a = SortedVec::from(vec![1, 2, 2, 4]);
+    /// let b = SortedVec::from(vec![2, 3, 4, 4]);
+    ///
+    /// assert_eq!(*a.union(&b), vec![1, 2, 2, 3, 4, 4]);
+    /// ```
+    pub fn union(&self, other: &Self) -> Self {
+        self.set_op(other, true, true, true)
+    }
+
+    /// Returns the intersection of `self` and `other`.
+    ///
+    /// As a multiset operation, each value appears as many times as in the one containing less of it.
+    ///
+    /// Complexity: $\mathcal{O}(N + M)$ comparisons and copies where
+    /// - $N$ and $M$ are the lengths of the sorted vectors
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let a = SortedVec::from(vec![1, 2, 2, 4]);
+    /// let b = SortedVec::from(vec![2, 3, 4, 4]);
+    ///
+    /// assert_eq!(*a.intersection(&b), vec![2, 4]);
+    /// ```
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.set_op(other, false, false, true)
+    }
+
+    /// Returns the elements of `self` which are not in `other`.
+    ///
+    /// As a multiset operation, occurrences of each value in `other` cancel out those in `self`.
+    ///
+    /// Complexity: $\mathcal{O}(N + M)$ comparisons and copies where
+    /// - $N$ and $M$ are the lengths of the sorted vectors
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let a = SortedVec::from(vec![1, 2, 2, 4]);
+    /// let b = SortedVec::from(vec![2, 3, 4, 4]);
+    ///
+    /// assert_eq!(*a.difference(&b), vec![1, 2]);
+    /// ```
+    pub fn difference(&self, other: &Self) -> Self {
+        self.set_op(other, true, false, false)
+    }
+
+    /// Returns the elements which are in exactly one of `self` and `other`.
+    ///
+    /// As a multiset operation, each value appears as many times as the difference of its occurrences.
+    ///
+    /// Complexity: $\mathcal{O}(N + M)$ comparisons and copies where
+    /// - $N$ and $M$ are the lengths of the sorted vectors
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cp_library::ds::sorted_vec::SortedVec;
+    ///
+    /// let a = SortedVec::from(vec![1, 2, 2, 4]);
+    /// let b = SortedVec::from(vec![2, 3, 4, 4]);
+    ///
+    /// assert_eq!(*a.symmetric_difference(&b), vec![1, 2, 3, 4]);
+    /// ```
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.set_op(other, true, true, false)
+    }
 }
 
 /// Conversion from a `Vec` by sorting its content
@@ -129,7 +466,7 @@ where
 {
     fn from(mut arr: Vec<T>) -> Self {
         arr.sort_unstable();
-        Self { arr }
+        Self::from_vec_unchecked(arr)
     }
 }
 
@@ -150,6 +487,7 @@ where
 {
     type Target = Vec<T>;
     fn deref(&self) -> &Self::Target {
+        self.validate();
         &self.arr
     }
 }
@@ -158,13 +496,44 @@ where
 ///
 /// # Warning
 ///
-/// No checks are made if the sorted vector is accessed through mutable deferencing.
+/// The mutations themselves are not checked.
 /// It is up to you to keep the vector sorted if you modify it through `Vec` methods!
+///
+/// Prefer the checked methods such as [`SortedVec::insert`] and [`SortedVec::remove_one`].
+///
+/// # Example
+///
+/// The vector may be temporarily unsorted between mutable accesses:
+///
+/// ```
+/// use cp_library::ds::sorted_vec::SortedVec;
+///
+/// let mut arr = SortedVec::from(vec![1, 2, 3]);
+/// arr[0] = 5;
+/// arr[1] = 6;
+/// arr[2] = 7;
+/// assert_eq!(*arr, vec![5, 6, 7]);
+/// ```
+///
+/// # Panics
+///
+/// Only in debug builds, sortedness is validated on the next read or checked method call
+/// after a mutable dereferencing:
+///
+/// ```should_panic
+/// use cp_library::ds::sorted_vec::SortedVec;
+///
+/// let mut arr = SortedVec::from(vec![1, 2, 3]);
+/// arr[0] = 5;
+/// let idx = arr.lower_bound(&2);
+/// ```
 impl<T> DerefMut for SortedVec<T>
 where
     T: Ord,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        #[cfg(debug_assertions)]
+        self.dirty.store(true, Ordering::Relaxed);
         &mut self.arr
     }
 }
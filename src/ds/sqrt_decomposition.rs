@@ -0,0 +1,353 @@
+use crate::ds::sorted_vec::SortedVec;
+use crate::math::algebra::Abelian;
+
+/// Summary of a block of a [`SqrtDecomposition`], together with the hooks used to maintain it
+///
+/// Each block also has a pending tag, kept by the container: when a tag is applied to a whole block
+/// it is composed with the pending one and [`Block::apply_block`] is called, while
+/// the values of the block are left untouched until the block is updated partially.
+pub trait Block {
+    /// The type of the elements of the array
+    type Value: Clone;
+    /// The type of the range updates
+    type Tag: Clone;
+
+    /// Builds the summary of a block containing `values`, with no pending tag
+    fn build(values: &[Self::Value]) -> Self;
+
+    /// Applies `tag` to a single element
+    fn apply_value(value: &mut Self::Value, tag: &Self::Tag);
+
+    /// Returns the tag equivalent to applying `first` and then `second`
+    fn compose(first: &Self::Tag, second: &Self::Tag) -> Self::Tag;
+
+    /// Updates the summary after `tag` is applied to the whole block
+    fn apply_block(&mut self, tag: &Self::Tag);
+}
+
+/// Array split into blocks of about $\sqrt N$ elements, each with a user-defined [`Block`] summary
+///
+/// Handles range updates and queries that cannot be expressed as monoid actions on a segment tree:
+/// whole blocks are handled through their summaries, while the elements of the blocks
+/// partially covered by a range are processed one by one.
+///
+/// # Example
+///
+/// Range add and range sum:
+///
+/// ```
+/// use cp_library::ds::sqrt_decomposition::{Block, SqrtDecomposition};
+///
+/// struct Sum {
+///     sum: i64,
+///     len: i64,
+/// }
+///
+/// impl Block for Sum {
+///     type Value = i64;
+///     type Tag = i64;
+///
+///     fn build(values: &[i64]) -> Self {
+///         Sum { sum: values.iter().sum(), len: values.len() as i64 }
+///     }
+///
+///     fn apply_value(value: &mut i64, tag: &i64) {
+///         *value += tag;
+///     }
+///
+///     fn compose(first: &i64, second: &i64) -> i64 {
+///         first + second
+///     }
+///
+///     fn apply_block(&mut self, tag: &i64) {
+///         self.sum += tag * self.len;
+///     }
+/// }
+///
+/// let mut arr = SqrtDecomposition::<Sum>::new(vec![1, 2, 3, 4, 5, 6, 7]);
+/// arr.update(1, 6, &10);
+///
+/// let sum = arr.query(0, 4, 0, |sum, v| sum + v, |sum, block, _| sum + block.sum);
+/// assert_eq!(sum, 40);
+/// ```
+pub struct SqrtDecomposition<B: Block> {
+    values: Vec<B::Value>,
+    blocks: Vec<B>,
+    tags: Vec<Option<B::Tag>>,
+    block_size: usize,
+}
+
+impl<B: Block> SqrtDecomposition<B> {
+    /// Builds the decomposition of `values`, with blocks of $\lfloor \sqrt N \rfloor$ elements
+    ///
+    /// Complexity: $\mathcal{O}(N)$ plus the calls to [`Block::build`] where:
+    /// - $N$ is the length of the array.
+    pub fn new(values: Vec<B::Value>) -> Self {
+        let block_size = values.len().isqrt().max(1);
+        Self::with_block_size(values, block_size)
+    }
+
+    /// Builds the decomposition of `values`, with blocks of `block_size` elements
+    ///
+    /// Complexity: $\mathcal{O}(N)$ plus the calls to [`Block::build`] where:
+    /// - $N$ is the length of the array.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `block_size` is $0$.
+    pub fn with_block_size(values: Vec<B::Value>, block_size: usize) -> Self {
+        debug_assert!(block_size > 0);
+
+        let blocks: Vec<_> = values.chunks(block_size).map(B::build).collect();
+        let tags = blocks.iter().map(|_| None).collect();
+
+        SqrtDecomposition {
+            values,
+            blocks,
+            tags,
+            block_size,
+        }
+    }
+
+    /// Returns the length of the array
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns [`true`] if the array is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the number of elements in each block
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    fn range(&self, block: usize) -> (usize, usize) {
+        let start = block * self.block_size;
+        (start, (start + self.block_size).min(self.values.len()))
+    }
+
+    // Applies the pending tag of `block` to its elements
+    fn push(&mut self, block: usize) {
+        if let Some(tag) = self.tags[block].take() {
+            let (l, r) = self.range(block);
+            for value in &mut self.values[l..r] {
+                B::apply_value(value, &tag);
+            }
+        }
+    }
+
+    fn rebuild(&mut self, block: usize) {
+        let (l, r) = self.range(block);
+        self.blocks[block] = B::build(&self.values[l..r]);
+    }
+
+    /// Returns the element at `pos`
+    ///
+    /// Complexity: $\mathcal{O}(1)$ plus a call to [`Block::apply_value`]
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is not a valid index.
+    pub fn get(&self, pos: usize) -> B::Value {
+        let mut value = self.values[pos].clone();
+        if let Some(tag) = &self.tags[pos / self.block_size] {
+            B::apply_value(&mut value, tag);
+        }
+        value
+    }
+
+    /// Replaces the element at `pos` with `val`
+    ///
+    /// Complexity: $\mathcal{O}(\sqrt N)$ plus the calls to the hooks where:
+    /// - $N$ is the length of the array.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is not a valid index.
+    pub fn set(&mut self, pos: usize, val: B::Value) {
+        let block = pos / self.block_size;
+        self.push(block);
+        self.values[pos] = val;
+        self.rebuild(block);
+    }
+
+    /// Applies `tag` to all the elements in the range $[l, r)$
+    ///
+    /// Complexity: $\mathcal{O}(\sqrt N)$ plus the calls to the hooks where:
+    /// - $N$ is the length of the array.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn update(&mut self, l: usize, r: usize, tag: &B::Tag) {
+        debug_assert!(l <= r && r <= self.values.len());
+        if l == r {
+            return;
+        }
+
+        let (first, last) = (l / self.block_size, (r - 1) / self.block_size);
+        for block in first..=last {
+            let (bl, br) = self.range(block);
+            if l <= bl && br <= r {
+                self.tags[block] = Some(match &self.tags[block] {
+                    Some(old) => B::compose(old, tag),
+                    None => tag.clone(),
+                });
+                self.blocks[block].apply_block(tag);
+            } else {
+                self.push(block);
+                for value in &mut self.values[l.max(bl)..r.min(br)] {
+                    B::apply_value(value, tag);
+                }
+                self.rebuild(block);
+            }
+        }
+    }
+
+    /// Folds the range $[l, r)$ from left to right, starting from `init`: `partial` is called on
+    /// each element of the blocks partially covered by the range, and `full` on the summary and
+    /// the pending tag of the blocks fully covered by it.
+    ///
+    /// Partially covered blocks get their pending tags applied, hence the mutable borrow.
+    ///
+    /// Complexity: $\mathcal{O}(\sqrt N)$ plus the calls to the hooks and closures where:
+    /// - $N$ is the length of the array.
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn query<A, P, F>(&mut self, l: usize, r: usize, init: A, mut partial: P, mut full: F) -> A
+    where
+        P: FnMut(A, &B::Value) -> A,
+        F: FnMut(A, &B, Option<&B::Tag>) -> A,
+    {
+        debug_assert!(l <= r && r <= self.values.len());
+        if l == r {
+            return init;
+        }
+
+        let mut acc = init;
+        let (first, last) = (l / self.block_size, (r - 1) / self.block_size);
+        for block in first..=last {
+            let (bl, br) = self.range(block);
+            if l <= bl && br <= r {
+                acc = full(acc, &self.blocks[block], self.tags[block].as_ref());
+            } else {
+                if self.tags[block].is_some() {
+                    self.push(block);
+                    self.rebuild(block);
+                }
+                acc = self.values[l.max(bl)..r.min(br)]
+                    .iter()
+                    .fold(acc, &mut partial);
+            }
+        }
+        acc
+    }
+}
+
+/// Range update on an [abelian group](https://en.wikipedia.org/wiki/Abelian_group) `T`,
+/// used as the tag of a [`SortedBlock`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Update<T> {
+    /// Adds the value to the elements
+    Add(T),
+    /// Replaces the elements with the value
+    Assign(T),
+}
+
+/// Block keeping its elements in a [`SortedVec`], for order statistics queries under
+/// range additions and assignments
+///
+/// `T` must be [`Ord`], [`Clone`] and [`Abelian`], and the group operation must preserve the order
+///
+/// # Example
+///
+/// Range add, range assign, and number of elements smaller than a value in a range:
+///
+/// ```
+/// use cp_library::ds::sqrt_decomposition::{SortedBlock, SqrtDecomposition, Update};
+///
+/// let mut arr = SqrtDecomposition::<SortedBlock<i64>>::new(vec![5, 1, 4, 2, 8, 3, 7, 6, 0]);
+/// arr.update(0, 5, &Update::Add(2));
+/// arr.update(6, 9, &Update::Assign(4));
+///
+/// // [7, 3, 6, 4, 10, 3, 4, 4, 4]
+/// let count = arr.query(
+///     1,
+///     9,
+///     0,
+///     |count, v| count + usize::from(*v < 5),
+///     |count, block, tag| count + block.count_less(tag, &5),
+/// );
+/// assert_eq!(count, 6);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SortedBlock<T> {
+    sorted: SortedVec<T>,
+}
+
+impl<T> SortedBlock<T>
+where
+    T: Ord + Clone + Abelian,
+{
+    /// Returns the sorted elements of the block, without the pending tag applied
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn sorted(&self) -> &SortedVec<T> {
+        &self.sorted
+    }
+
+    /// Returns the number of elements of the block smaller than `x`, once `tag` is applied
+    ///
+    /// Complexity: $\mathcal{O}(\log B)$ where:
+    /// - $B$ is the size of the block.
+    pub fn count_less(&self, tag: Option<&Update<T>>, x: &T) -> usize {
+        match tag {
+            None => self.sorted.lower_bound(x),
+            Some(Update::Add(c)) => self.sorted.lower_bound(&x.clone().op(c.clone().inv())),
+            Some(Update::Assign(c)) if c < x => self.sorted.len(),
+            Some(Update::Assign(_)) => 0,
+        }
+    }
+}
+
+impl<T> Block for SortedBlock<T>
+where
+    T: Ord + Clone + Abelian,
+{
+    type Value = T;
+    type Tag = Update<T>;
+
+    fn build(values: &[T]) -> Self {
+        SortedBlock {
+            sorted: SortedVec::from(values.to_vec()),
+        }
+    }
+
+    fn apply_value(value: &mut T, tag: &Update<T>) {
+        *value = match tag {
+            Update::Add(c) => value.clone().op(c.clone()),
+            Update::Assign(c) => c.clone(),
+        };
+    }
+
+    fn compose(first: &Update<T>, second: &Update<T>) -> Update<T> {
+        match (first, second) {
+            (Update::Add(a), Update::Add(b)) => Update::Add(a.clone().op(b.clone())),
+            (Update::Assign(a), Update::Add(b)) => Update::Assign(a.clone().op(b.clone())),
+            (_, Update::Assign(b)) => Update::Assign(b.clone()),
+        }
+    }
+
+    fn apply_block(&mut self, _tag: &Update<T>) {}
+}
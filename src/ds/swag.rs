@@ -0,0 +1,415 @@
+use crate::math::algebra::Monoid;
+
+/// Queue over a [monoid](https://en.wikipedia.org/wiki/Monoid) `T` supporting the fold of all its elements
+///
+/// Implemented with two stacks, each storing the folds of its elements,
+/// so that the fold is correct even for non-commutative monoids.
+///
+/// `T` must be [`Clone`] and [`Monoid`]
+///
+/// # Example
+///
+/// Sliding window minimum, with the minimum as a semigroup lifted to a monoid by [`Option`]:
+///
+/// ```
+/// use cp_library::ds::swag::FoldableQueue;
+/// use cp_library::math::algebra::{Magma, Semigroup};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Min(i32);
+///
+/// impl Magma for Min {
+///     fn op(self, other: Self) -> Self {
+///         Min(self.0.min(other.0))
+///     }
+/// }
+///
+/// impl Semigroup for Min {}
+///
+/// let arr = [4, 2, 12, 11, -5, 7];
+/// let mut window = FoldableQueue::new();
+/// let mut mins = vec![];
+///
+/// for (i, &val) in arr.iter().enumerate() {
+///     window.push(Some(Min(val)));
+///     if i >= 3 {
+///         window.pop();
+///     }
+///     if i >= 2 {
+///         mins.push(window.fold().unwrap().0);
+///     }
+/// }
+///
+/// assert_eq!(mins, vec![2, 2, -5, -5]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FoldableQueue<T> {
+    // Elements popped first are on top, each paired with the fold from itself to the bottom
+    front: Vec<(T, T)>,
+    // Elements pushed last are on top, each paired with the fold from the bottom to itself
+    back: Vec<(T, T)>,
+}
+
+impl<T> FoldableQueue<T>
+where
+    T: Clone + Monoid,
+{
+    /// Creates an empty queue
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableQueue;
+    ///
+    /// let x: FoldableQueue<i32> = FoldableQueue::new();
+    /// ```
+    pub fn new() -> Self {
+        FoldableQueue {
+            front: vec![],
+            back: vec![],
+        }
+    }
+
+    /// Returns the number of elements in the queue
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    /// Returns [`true`] if the queue is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+
+    /// Pushes `val` at the back of the queue
+    ///
+    /// Complexity: $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableQueue;
+    ///
+    /// let mut x = FoldableQueue::new();
+    /// x.push(3);
+    /// x.push(4);
+    /// assert_eq!(x.fold(), 7);
+    /// ```
+    pub fn push(&mut self, val: T) {
+        let agg = match self.back.last() {
+            Some((_, agg)) => agg.clone().op(val.clone()),
+            None => val.clone(),
+        };
+        self.back.push((val, agg));
+    }
+
+    /// Removes the element at the front of the queue and returns it, or [`None`] if the queue is empty
+    ///
+    /// Complexity: amortized $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableQueue;
+    ///
+    /// let mut x = FoldableQueue::new();
+    /// x.push(3);
+    /// x.push(4);
+    /// assert_eq!(x.pop(), Some(3));
+    /// assert_eq!(x.fold(), 4);
+    /// assert_eq!(x.pop(), Some(4));
+    /// assert_eq!(x.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        if self.front.is_empty() {
+            while let Some((val, _)) = self.back.pop() {
+                let agg = match self.front.last() {
+                    Some((_, agg)) => val.clone().op(agg.clone()),
+                    None => val.clone(),
+                };
+                self.front.push((val, agg));
+            }
+        }
+        self.front.pop().map(|(val, _)| val)
+    }
+
+    /// Returns a reference to the element at the front of the queue, or [`None`] if the queue is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableQueue;
+    ///
+    /// let mut x = FoldableQueue::new();
+    /// x.push(3);
+    /// x.push(4);
+    /// assert_eq!(x.front(), Some(&3));
+    /// ```
+    pub fn front(&self) -> Option<&T> {
+        self.front
+            .last()
+            .or_else(|| self.back.first())
+            .map(|(val, _)| val)
+    }
+
+    /// Returns the fold of all the elements of the queue, from front to back
+    ///
+    /// Complexity: $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableQueue;
+    /// use cp_library::math::algebra::instances::Affine;
+    ///
+    /// let mut x = FoldableQueue::new();
+    /// x.push(Affine::new(2, 1));
+    /// x.push(Affine::new(3, 0));
+    /// x.push(Affine::new(1, 5));
+    /// assert_eq!(x.fold().eval(1), 14);
+    /// x.pop();
+    /// assert_eq!(x.fold().eval(1), 8);
+    /// ```
+    pub fn fold(&self) -> T {
+        let front = self.front.last().map_or(T::ID, |(_, agg)| agg.clone());
+        let back = self.back.last().map_or(T::ID, |(_, agg)| agg.clone());
+        front.op(back)
+    }
+}
+
+impl<T> Default for FoldableQueue<T>
+where
+    T: Clone + Monoid,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deque over a [monoid](https://en.wikipedia.org/wiki/Monoid) `T` supporting the fold of all its elements
+///
+/// Implemented with two stacks, each storing the folds of its elements;
+/// when one of them runs out, the other one is split in half.
+/// The fold is correct even for non-commutative monoids.
+///
+/// `T` must be [`Clone`] and [`Monoid`]
+#[derive(Clone, Debug)]
+pub struct FoldableDeque<T> {
+    // Elements at the front are on top, each paired with the fold from itself to the bottom
+    front: Vec<(T, T)>,
+    // Elements at the back are on top, each paired with the fold from the bottom to itself
+    back: Vec<(T, T)>,
+}
+
+impl<T> FoldableDeque<T>
+where
+    T: Clone + Monoid,
+{
+    /// Creates an empty deque
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableDeque;
+    ///
+    /// let x: FoldableDeque<i32> = FoldableDeque::new();
+    /// ```
+    pub fn new() -> Self {
+        FoldableDeque {
+            front: vec![],
+            back: vec![],
+        }
+    }
+
+    /// Returns the number of elements in the deque
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    /// Returns [`true`] if the deque is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+
+    fn push_to_front(front: &mut Vec<(T, T)>, val: T) {
+        let agg = match front.last() {
+            Some((_, agg)) => val.clone().op(agg.clone()),
+            None => val.clone(),
+        };
+        front.push((val, agg));
+    }
+
+    fn push_to_back(back: &mut Vec<(T, T)>, val: T) {
+        let agg = match back.last() {
+            Some((_, agg)) => agg.clone().op(val.clone()),
+            None => val.clone(),
+        };
+        back.push((val, agg));
+    }
+
+    // Redistributes the elements so that the first `mid` of them are in the front stack
+    fn rebalance(&mut self, mid: usize) {
+        let vals: Vec<_> = self
+            .front
+            .drain(..)
+            .rev()
+            .chain(self.back.drain(..))
+            .map(|(val, _)| val)
+            .collect();
+
+        for val in vals[..mid].iter().rev() {
+            Self::push_to_front(&mut self.front, val.clone());
+        }
+        for val in vals[mid..].iter() {
+            Self::push_to_back(&mut self.back, val.clone());
+        }
+    }
+
+    /// Pushes `val` at the front of the deque
+    ///
+    /// Complexity: $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableDeque;
+    ///
+    /// let mut x = FoldableDeque::new();
+    /// x.push_front(3);
+    /// x.push_front(4);
+    /// assert_eq!(x.front(), Some(&4));
+    /// ```
+    pub fn push_front(&mut self, val: T) {
+        Self::push_to_front(&mut self.front, val);
+    }
+
+    /// Pushes `val` at the back of the deque
+    ///
+    /// Complexity: $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableDeque;
+    ///
+    /// let mut x = FoldableDeque::new();
+    /// x.push_back(3);
+    /// x.push_back(4);
+    /// assert_eq!(x.back(), Some(&4));
+    /// ```
+    pub fn push_back(&mut self, val: T) {
+        Self::push_to_back(&mut self.back, val);
+    }
+
+    /// Removes the element at the front of the deque and returns it, or [`None`] if the deque is empty
+    ///
+    /// Complexity: amortized $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableDeque;
+    ///
+    /// let mut x = FoldableDeque::new();
+    /// x.push_back(3);
+    /// x.push_back(4);
+    /// assert_eq!(x.pop_front(), Some(3));
+    /// assert_eq!(x.pop_front(), Some(4));
+    /// assert_eq!(x.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.front.is_empty() {
+            self.rebalance(self.back.len().div_ceil(2));
+        }
+        self.front.pop().map(|(val, _)| val)
+    }
+
+    /// Removes the element at the back of the deque and returns it, or [`None`] if the deque is empty
+    ///
+    /// Complexity: amortized $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableDeque;
+    ///
+    /// let mut x = FoldableDeque::new();
+    /// x.push_front(3);
+    /// x.push_front(4);
+    /// assert_eq!(x.pop_back(), Some(3));
+    /// assert_eq!(x.pop_back(), Some(4));
+    /// assert_eq!(x.pop_back(), None);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.back.is_empty() {
+            self.rebalance(self.front.len() / 2);
+        }
+        self.back.pop().map(|(val, _)| val)
+    }
+
+    /// Returns a reference to the element at the front of the deque, or [`None`] if the deque is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn front(&self) -> Option<&T> {
+        self.front
+            .last()
+            .or_else(|| self.back.first())
+            .map(|(val, _)| val)
+    }
+
+    /// Returns a reference to the element at the back of the deque, or [`None`] if the deque is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn back(&self) -> Option<&T> {
+        self.back
+            .last()
+            .or_else(|| self.front.first())
+            .map(|(val, _)| val)
+    }
+
+    /// Returns the fold of all the elements of the deque, from front to back
+    ///
+    /// Complexity: $\mathcal{O}(1)$ monoid operations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::swag::FoldableDeque;
+    /// use cp_library::math::algebra::instances::Affine;
+    ///
+    /// let mut x = FoldableDeque::new();
+    /// x.push_back(Affine::new(3, 0));
+    /// x.push_front(Affine::new(2, 1));
+    /// x.push_back(Affine::new(1, 5));
+    /// assert_eq!(x.fold().eval(1), 14);
+    /// x.pop_back();
+    /// assert_eq!(x.fold().eval(1), 9);
+    /// ```
+    pub fn fold(&self) -> T {
+        let front = self.front.last().map_or(T::ID, |(_, agg)| agg.clone());
+        let back = self.back.last().map_or(T::ID, |(_, agg)| agg.clone());
+        front.op(back)
+    }
+}
+
+impl<T> Default for FoldableDeque<T>
+where
+    T: Clone + Monoid,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,600 @@
+use crate::math::algebra::Monoid;
+use std::mem;
+
+/// Simple xorshift pseudorandom generator used for treap priorities
+pub(crate) fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    val: T,
+    agg: T,
+    rev_agg: T,
+    size: usize,
+    priority: u64,
+    rev: bool,
+    l: Link<T>,
+    r: Link<T>,
+}
+
+impl<T> Node<T>
+where
+    T: Clone + Monoid,
+{
+    fn new(val: T, priority: u64) -> Self {
+        Node {
+            agg: val.clone(),
+            rev_agg: val.clone(),
+            val,
+            size: 1,
+            priority,
+            rev: false,
+            l: None,
+            r: None,
+        }
+    }
+
+    // Reverses the subtree, leaving the children to be reversed lazily
+    fn toggle(&mut self) {
+        mem::swap(&mut self.l, &mut self.r);
+        mem::swap(&mut self.agg, &mut self.rev_agg);
+        self.rev ^= true;
+    }
+
+    fn push(&mut self) {
+        if self.rev {
+            for child in [&mut self.l, &mut self.r].into_iter().flatten() {
+                child.toggle();
+            }
+            self.rev = false;
+        }
+    }
+
+    fn pull(&mut self) {
+        self.size = size(&self.l) + 1 + size(&self.r);
+        self.agg = agg(&self.l).op(self.val.clone()).op(agg(&self.r));
+        self.rev_agg = rev_agg(&self.r).op(self.val.clone()).op(rev_agg(&self.l));
+    }
+}
+
+fn size<T>(t: &Link<T>) -> usize {
+    t.as_ref().map_or(0, |node| node.size)
+}
+
+fn agg<T: Clone + Monoid>(t: &Link<T>) -> T {
+    t.as_ref().map_or(T::ID, |node| node.agg.clone())
+}
+
+fn rev_agg<T: Clone + Monoid>(t: &Link<T>) -> T {
+    t.as_ref().map_or(T::ID, |node| node.rev_agg.clone())
+}
+
+fn merge<T: Clone + Monoid>(a: Link<T>, b: Link<T>) -> Link<T> {
+    match (a, b) {
+        (None, t) | (t, None) => t,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority > b.priority {
+                a.push();
+                a.r = merge(a.r.take(), Some(b));
+                a.pull();
+                Some(a)
+            } else {
+                b.push();
+                b.l = merge(Some(a), b.l.take());
+                b.pull();
+                Some(b)
+            }
+        }
+    }
+}
+
+fn split<T: Clone + Monoid>(t: Link<T>, k: usize) -> (Link<T>, Link<T>) {
+    match t {
+        None => (None, None),
+        Some(mut node) => {
+            node.push();
+            if size(&node.l) >= k {
+                let (l, r) = split(node.l.take(), k);
+                node.l = r;
+                node.pull();
+                (l, Some(node))
+            } else {
+                let (l, r) = split(node.r.take(), k - size(&node.l) - 1);
+                node.r = l;
+                node.pull();
+                (Some(node), r)
+            }
+        }
+    }
+}
+
+// Restores the heap property on priorities, assuming it holds in both subtrees
+fn heapify<T>(node: &mut Node<T>) {
+    let lp = node.l.as_ref().map_or(0, |c| c.priority);
+    let rp = node.r.as_ref().map_or(0, |c| c.priority);
+
+    let child = if lp >= rp { &mut node.l } else { &mut node.r };
+    if let Some(c) = child {
+        if c.priority > node.priority {
+            mem::swap(&mut node.priority, &mut c.priority);
+            heapify(c);
+        }
+    }
+}
+
+fn build<T: Clone + Monoid>(array: &[T], seed: &mut u64) -> Link<T> {
+    if array.is_empty() {
+        return None;
+    }
+
+    let mid = array.len() / 2;
+    let mut node = Box::new(Node::new(array[mid].clone(), xorshift(seed)));
+    node.l = build(&array[..mid], seed);
+    node.r = build(&array[mid + 1..], seed);
+    heapify(&mut node);
+    node.pull();
+    Some(node)
+}
+
+/// Implicit treap over a [monoid](https://en.wikipedia.org/wiki/Monoid) `T`
+///
+/// A sequence supporting insertions and removals at any position, range folds and range reversals.
+/// Since `T` need not be commutative, every node keeps the fold of its subtree in both directions,
+/// so that folds remain correct after reversals.
+///
+/// `T` must be [`Clone`] and [`Monoid`]
+#[derive(Clone, Debug)]
+pub struct ImplicitTreap<T> {
+    root: Link<T>,
+    seed: u64,
+}
+
+impl<T> ImplicitTreap<T>
+where
+    T: Clone + Monoid,
+{
+    /// Builds an empty treap
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let x: ImplicitTreap<i32> = ImplicitTreap::new();
+    /// assert!(x.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        ImplicitTreap {
+            root: None,
+            seed: SEED,
+        }
+    }
+
+    /// Builds a treap from a slice of `T`.
+    ///
+    /// Complexity: $\mathcal{O}(N)$ monoid operations where:
+    /// - $N$ is the length of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let x = ImplicitTreap::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.len(), 4);
+    /// ```
+    pub fn from(array: &[T]) -> Self {
+        let mut seed = SEED;
+        let root = build(array, &mut seed);
+        ImplicitTreap { root, seed }
+    }
+
+    /// Returns the number of elements in the treap
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns [`true`] if the treap contains no elements
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Inserts `val` at position `pos`, shifting all elements after it to the right.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3]);
+    /// x.insert(1, 5);
+    /// x.insert(4, 6);
+    /// assert_eq!(x.iter().copied().collect::<Vec<_>>(), vec![1, 5, 2, 3, 6]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is greater than the length of the treap.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3]);
+    /// x.insert(4, 5);
+    /// ```
+    pub fn insert(&mut self, pos: usize, val: T) {
+        assert!(pos <= self.len());
+
+        let node = Box::new(Node::new(val, xorshift(&mut self.seed)));
+        let (l, r) = split(self.root.take(), pos);
+        self.root = merge(merge(l, Some(node)), r);
+    }
+
+    /// Removes and returns the element at position `pos`, shifting all elements after it to the left.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3]);
+    /// assert_eq!(x.remove(1), 2);
+    /// assert_eq!(x.iter().copied().collect::<Vec<_>>(), vec![1, 3]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3]);
+    /// x.remove(3);
+    /// ```
+    pub fn remove(&mut self, pos: usize) -> T {
+        assert!(pos < self.len());
+
+        let (l, r) = split(self.root.take(), pos);
+        let (mid, r) = split(r, 1);
+        self.root = merge(l, r);
+        mid.unwrap().val
+    }
+
+    /// Returns the element at position `pos`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3, 4]);
+    /// x.reverse(0, 3);
+    /// assert_eq!(x.get(0), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let x = ImplicitTreap::from(&[1, 2, 3]);
+    /// x.get(3);
+    /// ```
+    pub fn get(&self, pos: usize) -> T {
+        assert!(pos < self.len());
+
+        let (mut t, mut pos, mut rev) = (&self.root, pos, false);
+        while let Some(node) = t {
+            let (first, second) = if rev {
+                (&node.r, &node.l)
+            } else {
+                (&node.l, &node.r)
+            };
+
+            let ls = size(first);
+            if pos == ls {
+                return node.val.clone();
+            }
+
+            rev ^= node.rev;
+            if pos < ls {
+                t = first;
+            } else {
+                (t, pos) = (second, pos - ls - 1);
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// Replaces the element at position `pos` with `val`
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3, 4]);
+    /// x.set(2, 10);
+    /// assert_eq!(x.fold(1, 4), 16);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is not a valid index.
+    pub fn set(&mut self, pos: usize, val: T) {
+        assert!(pos < self.len());
+
+        let (l, r) = split(self.root.take(), pos);
+        let (mid, r) = split(r, 1);
+        let mut mid = mid.unwrap();
+        mid.val = val;
+        mid.pull();
+        self.root = merge(merge(l, Some(mid)), r);
+    }
+
+    /// Returns the fold of the elements in the range $[l, r)$, in order.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let x = ImplicitTreap::from(&[1, 2, 3, 4]);
+    /// assert_eq!(x.fold(1, 3), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range:
+    /// ```should_panic
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let x = ImplicitTreap::from(&[1, 2, 3, 4]);
+    /// x.fold(3, 2);
+    /// ```
+    pub fn fold(&self, l: usize, r: usize) -> T {
+        debug_assert!(l <= r && r <= self.len());
+        fold_rec(&self.root, false, l, r)
+    }
+
+    /// Reverses the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// With a non-commutative monoid, folds take into account the reversal:
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    /// use cp_library::math::algebra::instances::Affine;
+    ///
+    /// let f = Affine::new(2, 0);
+    /// let g = Affine::new(1, 3);
+    ///
+    /// let mut x = ImplicitTreap::from(&[f, g, f]);
+    /// assert_eq!(x.fold(0, 2).eval(1), 5);
+    ///
+    /// x.reverse(0, 2);
+    /// assert_eq!(x.fold(0, 2).eval(1), 8);
+    /// assert_eq!(x.fold(0, 3).eval(1), 16);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn reverse(&mut self, l: usize, r: usize) {
+        debug_assert!(l <= r && r <= self.len());
+
+        let (a, b) = split(self.root.take(), l);
+        let (mut b, c) = split(b, r - l);
+        if let Some(node) = b.as_mut() {
+            node.toggle();
+        }
+        self.root = merge(merge(a, b), c);
+    }
+
+    /// Rotates the range $[l, r)$ in-place such that the element at position $l + k$ becomes the first one.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3, 4, 5]);
+    /// x.rotate_left(1, 5, 1);
+    /// assert_eq!(x.iter().copied().collect::<Vec<_>>(), vec![1, 3, 4, 5, 2]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range, or if `k` is greater than $r - l$.
+    pub fn rotate_left(&mut self, l: usize, r: usize, k: usize) {
+        debug_assert!(l <= r && r <= self.len() && k <= r - l);
+
+        let (a, b) = split(self.root.take(), l);
+        let (b, d) = split(b, r - l);
+        let (b, c) = split(b, k);
+        self.root = merge(merge(a, c), merge(b, d));
+    }
+
+    /// Splits the treap in two at the given position,
+    /// returning a treap containing the elements from `at` onwards.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3, 4, 5]);
+    /// let y = x.split_off(2);
+    /// assert_eq!(x.fold(0, 2), 3);
+    /// assert_eq!(y.fold(0, 3), 12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `at` is greater than the length of the treap.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len());
+
+        let (l, r) = split(self.root.take(), at);
+        self.root = l;
+        ImplicitTreap {
+            root: r,
+            seed: xorshift(&mut self.seed),
+        }
+    }
+
+    /// Moves all the elements of `other` at the end of `self`, leaving `other` empty.
+    ///
+    /// Complexity: $\mathcal{O}(\log N)$ expected monoid operations where:
+    /// - $N$ is the total length of the treaps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2]);
+    /// let mut y = ImplicitTreap::from(&[3, 4, 5]);
+    /// x.append(&mut y);
+    /// assert_eq!(x.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    /// assert!(y.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    /// Returns an iterator over the elements of the treap, in order
+    ///
+    /// Complexity: $\mathcal{O}(N)$ for a complete iteration where:
+    /// - $N$ is the length of the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::treap::ImplicitTreap;
+    ///
+    /// let mut x = ImplicitTreap::from(&[1, 2, 3, 4, 5]);
+    /// x.reverse(1, 4);
+    /// assert_eq!(x.iter().copied().collect::<Vec<_>>(), vec![1, 4, 3, 2, 5]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_spine(&self.root, false);
+        iter
+    }
+}
+
+impl<T> Default for ImplicitTreap<T>
+where
+    T: Clone + Monoid,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Folds the range [l, r) of the subtree, which is reversed if `rev` is set
+fn fold_rec<T: Clone + Monoid>(t: &Link<T>, rev: bool, l: usize, r: usize) -> T {
+    let node = match t {
+        Some(node) if l < r => node,
+        _ => return T::ID,
+    };
+    if l == 0 && r == node.size {
+        return if rev {
+            node.rev_agg.clone()
+        } else {
+            node.agg.clone()
+        };
+    }
+
+    let (first, second) = if rev {
+        (&node.r, &node.l)
+    } else {
+        (&node.l, &node.r)
+    };
+    let child_rev = rev ^ node.rev;
+    let ls = size(first);
+
+    let mut ans = fold_rec(first, child_rev, l, r.min(ls));
+    if l <= ls && ls < r {
+        ans = ans.op(node.val.clone());
+    }
+    if r > ls + 1 {
+        ans = ans.op(fold_rec(
+            second,
+            child_rev,
+            l.max(ls + 1) - ls - 1,
+            r - ls - 1,
+        ));
+    }
+
+    ans
+}
+
+/// In-order iterator over the elements of an [`ImplicitTreap`]
+pub struct Iter<'a, T> {
+    stack: Vec<(&'a Node<T>, bool)>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_spine(&mut self, mut t: &'a Link<T>, mut rev: bool) {
+        while let Some(node) = t {
+            self.stack.push((node, rev));
+            t = if rev { &node.r } else { &node.l };
+            rev ^= node.rev;
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, rev) = self.stack.pop()?;
+        self.push_spine(if rev { &node.l } else { &node.r }, rev ^ node.rev);
+        Some(&node.val)
+    }
+}
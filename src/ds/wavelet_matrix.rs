@@ -0,0 +1,588 @@
+use crate::binsearch::first_true;
+use crate::ds::fenwick::Fenwick;
+use crate::math::algebra::Abelian;
+use std::cmp;
+use std::ops::Deref;
+
+// Bit vector with constant time rank, through per-word prefix counts
+#[derive(Clone, Debug)]
+struct BitVector {
+    words: Vec<u64>,
+    ranks: Vec<usize>,
+}
+
+impl BitVector {
+    fn new(bits: &[bool]) -> Self {
+        let mut words = vec![0u64; bits.len() / 64 + 1];
+        for (i, _) in bits.iter().enumerate().filter(|(_, &b)| b) {
+            words[i / 64] |= 1 << (i % 64);
+        }
+
+        let mut ranks = vec![0; words.len() + 1];
+        for (i, w) in words.iter().enumerate() {
+            ranks[i + 1] = ranks[i] + w.count_ones() as usize;
+        }
+
+        BitVector { words, ranks }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        (self.words[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    // Number of ones in [0, i)
+    fn rank1(&self, i: usize) -> usize {
+        let mask = (1u64 << (i % 64)) - 1;
+        self.ranks[i / 64] + (self.words[i / 64] & mask).count_ones() as usize
+    }
+
+    // Number of zeros in [0, i)
+    fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+
+    // Position of the k-th one (0-indexed)
+    fn select1(&self, k: usize) -> usize {
+        let w = first_true(0, self.words.len(), |w| self.ranks[w] > k) - 1;
+        64 * w + select_in_word(self.words[w], k - self.ranks[w])
+    }
+
+    // Position of the k-th zero (0-indexed)
+    fn select0(&self, k: usize) -> usize {
+        let w = first_true(0, self.words.len(), |w| 64 * w - self.ranks[w] > k) - 1;
+        64 * w + select_in_word(!self.words[w], k - (64 * w - self.ranks[w]))
+    }
+}
+
+fn select_in_word(mut word: u64, k: usize) -> usize {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
+
+/// Wavelet matrix over `u64` values
+///
+/// A static sequence supporting rank, select, k-th smallest and range frequency queries.
+/// Larger or non-integer values can be mapped to small integers through a
+/// [`CoordinateCompressor`](crate::ds::coord_comp::CoordinateCompressor) first.
+#[derive(Clone, Debug)]
+pub struct WaveletMatrix {
+    levels: Vec<BitVector>,
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl WaveletMatrix {
+    /// Builds a wavelet matrix from a slice of values
+    ///
+    /// Complexity: $\mathcal{O}(N \log \sigma)$ where:
+    /// - $N$ is the length of the slice;
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.len(), 12);
+    /// ```
+    ///
+    /// With coordinate compression:
+    ///
+    /// ```
+    /// use cp_library::ds::coord_comp::CoordinateCompressor;
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let arr = vec![-1_000_000_000i64, 7, 1_000_000_000, 7];
+    /// let comp = CoordinateCompressor::from_coords(arr.clone());
+    /// let compressed: Vec<_> = arr.iter().map(|x| comp.compress(x) as u64).collect();
+    ///
+    /// let wm = WaveletMatrix::from(&compressed);
+    /// assert_eq!(comp.decompress(wm.kth_smallest(0, 4, 3) as usize), 1_000_000_000);
+    /// ```
+    pub fn from(array: &[u64]) -> Self {
+        let max = array.iter().copied().max().unwrap_or(0);
+        let bits = cmp::max(64 - max.leading_zeros() as usize, 1);
+
+        let mut cur = array.to_vec();
+        let mut levels = Vec::with_capacity(bits);
+        let mut zeros = Vec::with_capacity(bits);
+
+        for level in (0..bits).rev() {
+            let bit: Vec<bool> = cur.iter().map(|&x| (x >> level) & 1 == 1).collect();
+            levels.push(BitVector::new(&bit));
+            zeros.push(bit.iter().filter(|&&b| !b).count());
+
+            let (mut lo, hi): (Vec<u64>, Vec<u64>) =
+                cur.iter().partition(|&&x| (x >> level) & 1 == 0);
+            lo.extend(hi);
+            cur = lo;
+        }
+
+        WaveletMatrix {
+            levels,
+            zeros,
+            len: array.len(),
+        }
+    }
+
+    fn bits(&self) -> usize {
+        self.levels.len()
+    }
+
+    // Maps the range [l, r) at depth d to the range at depth d + 1 containing the elements with the given bit
+    fn descend(&self, d: usize, l: usize, r: usize, bit: bool) -> (usize, usize) {
+        let level = &self.levels[d];
+        if bit {
+            (
+                self.zeros[d] + level.rank1(l),
+                self.zeros[d] + level.rank1(r),
+            )
+        } else {
+            (level.rank0(l), level.rank0(r))
+        }
+    }
+
+    fn bit_of(&self, x: u64, d: usize) -> bool {
+        (x >> (self.bits() - 1 - d)) & 1 == 1
+    }
+
+    fn in_range(&self, x: u64) -> bool {
+        self.bits() == 64 || x >> self.bits() == 0
+    }
+
+    /// Returns the length of the sequence
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns [`true`] if the sequence is empty
+    ///
+    /// Complexity: $\mathcal{O}(1)$
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the element at position `pos`
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma)$ where:
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.access(7), 6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5]);
+    /// wm.access(3);
+    /// ```
+    pub fn access(&self, pos: usize) -> u64 {
+        debug_assert!(pos < self.len);
+
+        let mut pos = pos;
+        let mut ans = 0;
+        for d in 0..self.bits() {
+            let bit = self.levels[d].get(pos);
+            ans = (ans << 1) | bit as u64;
+            pos = self.descend(d, pos, pos, bit).0;
+        }
+        ans
+    }
+
+    /// Returns the number of occurrences of `x` in the range $[0, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma)$ where:
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.rank(5, 9), 4);
+    /// assert_eq!(wm.rank(7, 9), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `r` is greater than the length of the sequence.
+    pub fn rank(&self, x: u64, r: usize) -> usize {
+        debug_assert!(r <= self.len);
+
+        if !self.in_range(x) {
+            return 0;
+        }
+
+        let (mut l, mut r) = (0, r);
+        for d in 0..self.bits() {
+            (l, r) = self.descend(d, l, r, self.bit_of(x, d));
+        }
+        r - l
+    }
+
+    /// Returns the position of the `k`-th (0-indexed) occurrence of `x`, if it exists.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma \log N)$ where:
+    /// - $N$ is the length of the sequence;
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.select(5, 0), Some(0));
+    /// assert_eq!(wm.select(5, 3), Some(6));
+    /// assert_eq!(wm.select(5, 5), None);
+    /// assert_eq!(wm.select(7, 0), None);
+    /// ```
+    pub fn select(&self, x: u64, k: usize) -> Option<usize> {
+        if !self.in_range(x) {
+            return None;
+        }
+
+        let (mut l, mut r) = (0, self.len);
+        for d in 0..self.bits() {
+            (l, r) = self.descend(d, l, r, self.bit_of(x, d));
+        }
+        if l + k >= r {
+            return None;
+        }
+
+        let mut pos = l + k;
+        for d in (0..self.bits()).rev() {
+            pos = if self.bit_of(x, d) {
+                self.levels[d].select1(pos - self.zeros[d])
+            } else {
+                self.levels[d].select0(pos)
+            };
+        }
+        Some(pos)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest element in the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma)$ where:
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.kth_smallest(2, 8, 0), 1);
+    /// assert_eq!(wm.kth_smallest(2, 8, 3), 5);
+    /// assert_eq!(wm.kth_smallest(2, 8, 5), 6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range, or `k` is not smaller than $r - l$.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// wm.kth_smallest(2, 8, 6);
+    /// ```
+    pub fn kth_smallest(&self, l: usize, r: usize, k: usize) -> u64 {
+        debug_assert!(l <= r && r <= self.len && k < r - l);
+
+        let (mut l, mut r, mut k) = (l, r, k);
+        let mut ans = 0;
+        for d in 0..self.bits() {
+            let (l0, r0) = self.descend(d, l, r, false);
+            if k < r0 - l0 {
+                (l, r) = (l0, r0);
+                ans <<= 1;
+            } else {
+                k -= r0 - l0;
+                (l, r) = self.descend(d, l, r, true);
+                ans = (ans << 1) | 1;
+            }
+        }
+        ans
+    }
+
+    /// Returns the `k`-th (0-indexed) largest element in the range $[l, r)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma)$ where:
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.kth_largest(2, 8, 0), 6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range, or `k` is not smaller than $r - l$.
+    pub fn kth_largest(&self, l: usize, r: usize, k: usize) -> u64 {
+        debug_assert!(l <= r && r <= self.len && k < r - l);
+        self.kth_smallest(l, r, r - l - 1 - k)
+    }
+
+    // Number of elements smaller than x in [l, r)
+    fn count_less(&self, l: usize, r: usize, x: u64) -> usize {
+        if !self.in_range(x) {
+            return r - l;
+        }
+
+        let (mut l, mut r) = (l, r);
+        let mut ans = 0;
+        for d in 0..self.bits() {
+            let bit = self.bit_of(x, d);
+            if bit {
+                let (l0, r0) = self.descend(d, l, r, false);
+                ans += r0 - l0;
+            }
+            (l, r) = self.descend(d, l, r, bit);
+        }
+        ans
+    }
+
+    /// Returns the number of elements in the range $[l, r)$ whose value is in $[lo, hi)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma)$ where:
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.range_freq(1, 10, 2, 5), 3);
+    /// assert_eq!(wm.range_freq(1, 10, 5, 2), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn range_freq(&self, l: usize, r: usize, lo: u64, hi: u64) -> usize {
+        debug_assert!(l <= r && r <= self.len);
+        if lo >= hi {
+            return 0;
+        }
+        self.count_less(l, r, hi) - self.count_less(l, r, lo)
+    }
+
+    /// Returns the largest element smaller than `x` in the range $[l, r)$, if any.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma)$ where:
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.prev_value(1, 10, 5), Some(4));
+    /// assert_eq!(wm.prev_value(1, 10, 1), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn prev_value(&self, l: usize, r: usize, x: u64) -> Option<u64> {
+        debug_assert!(l <= r && r <= self.len);
+        let cnt = self.count_less(l, r, x);
+        (cnt > 0).then(|| self.kth_smallest(l, r, cnt - 1))
+    }
+
+    /// Returns the smallest element not smaller than `x` in the range $[l, r)$, if any.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma)$ where:
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WaveletMatrix;
+    ///
+    /// let wm = WaveletMatrix::from(&[5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0]);
+    /// assert_eq!(wm.next_value(1, 10, 5), Some(5));
+    /// assert_eq!(wm.next_value(1, 10, 7), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn next_value(&self, l: usize, r: usize, x: u64) -> Option<u64> {
+        debug_assert!(l <= r && r <= self.len);
+        let cnt = self.count_less(l, r, x);
+        (cnt < r - l).then(|| self.kth_smallest(l, r, cnt))
+    }
+}
+
+/// Wavelet matrix over `u64` values, where each element also carries a weight
+/// in an [abelian group](https://en.wikipedia.org/wiki/Abelian_group) `T`
+///
+/// Every level keeps a [`Fenwick`] tree of the weights, so that weights can be updated
+/// and summed over the elements in a range whose value is in a given range.
+/// All queries of [`WaveletMatrix`] are available through dereferencing.
+///
+/// `T` must be [`Clone`] and [`Abelian`]
+#[derive(Clone, Debug)]
+pub struct WeightedWaveletMatrix<T> {
+    matrix: WaveletMatrix,
+    sums: Vec<Fenwick<T>>,
+}
+
+impl<T> WeightedWaveletMatrix<T>
+where
+    T: Clone + Abelian,
+{
+    /// Builds a weighted wavelet matrix from a slice of values and their weights
+    ///
+    /// Complexity: $\mathcal{O}(N \log \sigma)$ where:
+    /// - $N$ is the length of the slice;
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WeightedWaveletMatrix;
+    ///
+    /// let wm = WeightedWaveletMatrix::from(&[3, 1, 4, 1, 5], &[3, 1, 4, 1, 5]);
+    /// assert_eq!(wm.kth_smallest(0, 5, 2), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the slices have different lengths.
+    ///
+    /// ```should_panic
+    /// use cp_library::ds::wavelet_matrix::WeightedWaveletMatrix;
+    ///
+    /// let wm = WeightedWaveletMatrix::from(&[3, 1, 4, 1, 5], &[3, 1, 4, 1]);
+    /// ```
+    pub fn from(array: &[u64], weights: &[T]) -> Self {
+        assert_eq!(array.len(), weights.len());
+
+        let matrix = WaveletMatrix::from(array);
+        let mut cur: Vec<(u64, T)> = array.iter().copied().zip(weights.iter().cloned()).collect();
+        let mut sums = Vec::with_capacity(matrix.bits());
+
+        for level in (0..matrix.bits()).rev() {
+            let (mut lo, hi): (Vec<_>, Vec<_>) =
+                cur.into_iter().partition(|(x, _)| (x >> level) & 1 == 0);
+            lo.extend(hi);
+            cur = lo;
+
+            let level_weights: Vec<T> = cur.iter().map(|(_, w)| w.clone()).collect();
+            sums.push(Fenwick::from(&level_weights));
+        }
+
+        WeightedWaveletMatrix { matrix, sums }
+    }
+
+    /// Adds `w` to the weight of the element at position `pos`
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma \log N)$ where:
+    /// - $N$ is the length of the sequence;
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WeightedWaveletMatrix;
+    ///
+    /// let mut wm = WeightedWaveletMatrix::from(&[3, 1, 4, 1, 5], &[3, 1, 4, 1, 5]);
+    /// wm.add(2, 10);
+    /// assert_eq!(wm.range_sum(0, 5, 4, 5), 14);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `pos` is not a valid index.
+    pub fn add(&mut self, pos: usize, w: T) {
+        debug_assert!(pos < self.matrix.len);
+
+        let mut pos = pos;
+        for d in 0..self.matrix.bits() {
+            let bit = self.matrix.levels[d].get(pos);
+            pos = self.matrix.descend(d, pos, pos, bit).0;
+            self.sums[d].add(pos, w.clone());
+        }
+    }
+
+    // Sum of the weights of the elements smaller than x in [l, r)
+    fn sum_less(&self, l: usize, r: usize, x: u64) -> T {
+        if !self.matrix.in_range(x) {
+            let (l0, r0) = self.matrix.descend(0, l, r, false);
+            let (l1, r1) = self.matrix.descend(0, l, r, true);
+            return self.sums[0].query(l0, r0).op(self.sums[0].query(l1, r1));
+        }
+
+        let (mut l, mut r) = (l, r);
+        let mut ans = T::ID;
+        for d in 0..self.matrix.bits() {
+            let bit = self.matrix.bit_of(x, d);
+            if bit {
+                let (l0, r0) = self.matrix.descend(d, l, r, false);
+                ans = ans.op(self.sums[d].query(l0, r0));
+            }
+            (l, r) = self.matrix.descend(d, l, r, bit);
+        }
+        ans
+    }
+
+    /// Returns the sum of the weights of the elements in the range $[l, r)$ whose value is in $[lo, hi)$.
+    ///
+    /// Complexity: $\mathcal{O}(\log \sigma \log N)$ where:
+    /// - $N$ is the length of the sequence;
+    /// - $\sigma$ is the maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cp_library::ds::wavelet_matrix::WeightedWaveletMatrix;
+    ///
+    /// let wm = WeightedWaveletMatrix::from(&[3, 1, 4, 1, 5], &[30, 10, 40, 10, 50]);
+    /// assert_eq!(wm.range_sum(1, 5, 1, 5), 60);
+    /// assert_eq!(wm.range_sum(0, 5, 0, 100), 140);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Only in debug builds, if `l` and `r` do not specify a valid range.
+    pub fn range_sum(&self, l: usize, r: usize, lo: u64, hi: u64) -> T {
+        debug_assert!(l <= r && r <= self.matrix.len);
+        if lo >= hi {
+            return T::ID;
+        }
+        self.sum_less(l, r, hi).op(self.sum_less(l, r, lo).inv())
+    }
+}
+
+/// Immutably accesses the underlying [`WaveletMatrix`] to provide its queries
+impl<T> Deref for WeightedWaveletMatrix<T> {
+    type Target = WaveletMatrix;
+    fn deref(&self) -> &Self::Target {
+        &self.matrix
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::ops::{Add, Mul};
 
 impl<T: Magma> Magma for Option<T> {
     fn op(self, other: Self) -> Self {
@@ -70,3 +71,144 @@ impl_abelian_for_num!(isize);
 
 impl_abelian_for_num!(f32);
 impl_abelian_for_num!(f64);
+
+/// An affine function $x \mapsto a x + b$, with composition as the monoid operation.
+///
+/// The operation follows the order in which the functions are applied:
+/// `f.op(g)` is the function $x \mapsto g(f(x))$.
+/// This way, folding a range of functions with a segment tree gives the map
+/// obtained by applying them from left to right.
+///
+/// # Examples
+///
+/// ```
+/// use cp_library::ds::segtree::SegTree;
+/// use cp_library::math::algebra::Magma;
+/// use cp_library::math::algebra::instances::Affine;
+///
+/// let f = Affine::new(2i64, 1);
+/// let g = Affine::new(3, 0);
+///
+/// assert_eq!(f.op(g).eval(5), 33);
+/// assert_eq!(g.op(f).eval(5), 31);
+///
+/// let maps = SegTree::from(&[f, g, Affine::new(1, -4)]);
+/// assert_eq!(maps.query(0, 3).eval(5), 29);
+/// assert_eq!(maps.query(1, 3).eval(5), 11);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Affine<T> {
+    /// The linear coefficient
+    pub a: T,
+    /// The constant term
+    pub b: T,
+}
+
+impl<T> Affine<T> {
+    /// Creates the function $x \mapsto a x + b$
+    pub fn new(a: T, b: T) -> Self {
+        Affine { a, b }
+    }
+}
+
+impl<T> Affine<T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T>,
+{
+    /// Evaluates the function at `x`
+    pub fn eval(&self, x: T) -> T {
+        self.a * x + self.b
+    }
+}
+
+/// The sum of a range together with its length.
+///
+/// This is the [`Monoid`] an [`Affine`] function acts on in a range-affine/range-sum setting,
+/// since applying $x \mapsto a x + b$ to each element of a range of length $l$ and sum $s$
+/// yields a range of sum $a s + b l$.
+///
+/// # Examples
+///
+/// ```
+/// use cp_library::math::algebra::{Action, Magma};
+/// use cp_library::math::algebra::instances::{Affine, SumLen};
+///
+/// let range = SumLen { sum: 3, len: 1 }.op(SumLen { sum: 4, len: 1 });
+/// assert_eq!(range, SumLen { sum: 7, len: 2 });
+///
+/// let range = Affine::new(2, 1).act(range);
+/// assert_eq!(range, SumLen { sum: 16, len: 2 });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SumLen<T> {
+    /// The sum of the range
+    pub sum: T,
+    /// The length of the range
+    pub len: T,
+}
+
+macro_rules! impl_affine_for_num {
+    ($type:ty) => {
+        impl Magma for Affine<$type> {
+            fn op(self, other: Self) -> Self {
+                Affine {
+                    a: other.a * self.a,
+                    b: other.a * self.b + other.b,
+                }
+            }
+        }
+
+        impl Semigroup for Affine<$type> {}
+
+        impl Monoid for Affine<$type> {
+            const ID: Self = Affine {
+                a: 1 as $type,
+                b: 0 as $type,
+            };
+        }
+
+        impl Magma for SumLen<$type> {
+            fn op(self, other: Self) -> Self {
+                SumLen {
+                    sum: self.sum + other.sum,
+                    len: self.len + other.len,
+                }
+            }
+        }
+
+        impl Semigroup for SumLen<$type> {}
+
+        impl Monoid for SumLen<$type> {
+            const ID: Self = SumLen {
+                sum: 0 as $type,
+                len: 0 as $type,
+            };
+        }
+
+        impl Action<SumLen<$type>> for Affine<$type> {
+            fn act(self, x: SumLen<$type>) -> SumLen<$type> {
+                SumLen {
+                    sum: self.a * x.sum + self.b * x.len,
+                    len: x.len,
+                }
+            }
+        }
+    };
+}
+
+impl_affine_for_num!(i8);
+impl_affine_for_num!(i16);
+impl_affine_for_num!(i32);
+impl_affine_for_num!(i64);
+impl_affine_for_num!(i128);
+impl_affine_for_num!(isize);
+
+impl_affine_for_num!(u8);
+impl_affine_for_num!(u16);
+impl_affine_for_num!(u32);
+impl_affine_for_num!(u64);
+impl_affine_for_num!(u128);
+impl_affine_for_num!(usize);
+
+impl_affine_for_num!(f32);
+impl_affine_for_num!(f64);
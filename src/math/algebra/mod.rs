@@ -33,3 +33,17 @@ pub trait Group: Monoid {
 ///
 /// This trait is implemented for all signed builtin numeric types, with addition as the operation
 pub trait Abelian: Group {}
+
+/// A (right) [monoid action](https://en.wikipedia.org/wiki/Semigroup_action) of `Self` on a [`Monoid`] `T`
+///
+/// Implementations must satisfy, for all `f`, `g` and `x`, `y`:
+/// - `Self::ID.act(x) == x`;
+/// - `f.op(g).act(x) == g.act(f.act(x))`, i.e. `f.op(g)` applies `f` first and then `g`;
+/// - `f.act(x.op(y)) == f.act(x).op(f.act(y))`.
+///
+/// These are exactly the conditions required to use `Self` as the lazy tag
+/// of a segment tree over `T`.
+pub trait Action<T: Monoid>: Monoid {
+    /// Applies `self` to the element `x`
+    fn act(self, x: T) -> T;
+}